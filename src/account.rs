@@ -0,0 +1,97 @@
+use crate::*;
+use crate::nft_core::NonFungibleTokenCore;
+
+//maximum amount of active and of expired tokens listed in the overview of an account
+pub const MAX_OVERVIEW_TOKENS: usize = 50;
+
+//period covered by the active subscriptions of an account, from the earliest start to the latest expiration
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessWindow {
+    pub starts_at: Option<U64>,
+    //none if the account has a permanent subscription
+    pub expires_at: Option<U64>,
+    pub permanent: bool,
+}
+
+//everything a frontend needs to show the subscriptions of an account
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountOverview {
+    pub account_id: AccountId,
+    //subscriptions paid and not minted yet, per type
+    pub pending_subscriptions: TokensCounter,
+    pub active_tokens: Vec<JsonToken>,
+    pub expired_tokens: Vec<JsonToken>,
+    //none if the account has no active subscription
+    pub access_window: Option<AccessWindow>,
+    pub storage_balance: Option<StorageBalance>,
+    //storage of the account paid by the storage pool
+    pub sponsored_storage: U128,
+    //latest payments of the account, newest first
+    pub recent_receipts: Vec<Receipt>,
+    //USDT.e earned by referring other accounts. Always 0 until the contract has a referral program
+    pub referral_balance: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    //get the subscriptions, tokens and balances of an account in a single view call
+    pub fn get_account_overview(&self, account_id: AccountId) -> AccountOverview {
+        let now = now_ms();
+        let mut active_tokens = vec![];
+        let mut expired_tokens = vec![];
+        let mut access_window: Option<AccessWindow> = None;
+
+        if let Some(tokens) = self.tokens_per_owner.get(&account_id) {
+            //newest tokens first
+            for token_id in tokens.as_vector().iter().rev() {
+                let token = self.nft_token(token_id).unwrap();
+                let starts_at = token.metadata.starts_at;
+                let expires_at = token.metadata.expires_at;
+
+                if expires_at.map(|expires_at| expires_at <= now).unwrap_or(false) {
+                    if expired_tokens.len() < MAX_OVERVIEW_TOKENS {
+                        expired_tokens.push(token);
+                    }
+                    continue;
+                }
+
+                //widen the access window with the active token
+                access_window = Some(match access_window {
+                    None => AccessWindow {
+                        starts_at: starts_at.map(U64),
+                        expires_at: expires_at.map(U64),
+                        permanent: expires_at.is_none(),
+                    },
+                    Some(window) => AccessWindow {
+                        starts_at: match (window.starts_at, starts_at) {
+                            (Some(current), Some(starts_at)) => Some(U64(std::cmp::min(current.0, starts_at))),
+                            (current, starts_at) => current.or(starts_at.map(U64)),
+                        },
+                        expires_at: match (window.expires_at, expires_at) {
+                            (Some(current), Some(expires_at)) => Some(U64(std::cmp::max(current.0, expires_at))),
+                            _ => None,
+                        },
+                        permanent: window.permanent || expires_at.is_none(),
+                    },
+                });
+                if active_tokens.len() < MAX_OVERVIEW_TOKENS {
+                    active_tokens.push(token);
+                }
+            }
+        }
+
+        AccountOverview {
+            pending_subscriptions: self.internal_pending_subscriptions(&account_id),
+            active_tokens,
+            expired_tokens,
+            access_window,
+            storage_balance: self.storage_balances.get(&account_id),
+            sponsored_storage: U128(self.sponsored_storage_per_account.get(&account_id).unwrap_or(0)),
+            recent_receipts: self.internal_recent_receipts(&account_id),
+            referral_balance: U128(0),
+            account_id,
+        }
+    }
+}
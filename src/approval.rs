@@ -1,7 +1,7 @@
 use crate::*;
 use near_sdk::{ext_contract};
 
-pub trait NonFungibleTokenCore {
+pub trait NonFungibleTokenApproval {
     //approve an account ID to transfer a token on your behalf
     fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>);
 
@@ -33,16 +33,20 @@ trait NonFungibleTokenApprovalsReceiver {
 }
 
 #[near_bindgen]
-impl NonFungibleTokenCore for Contract {
+impl NonFungibleTokenApproval for Contract {
 
     //allow a specific account ID to approve a token on your behalf
     #[payable]
     fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>) {
         /*
             assert at least one yocto for security reasons - this will cause a redirect to the NEAR wallet.
-            The user needs to attach enough to pay for storage on the contract
+            The storage is paid out of the owner's storage balance (see storage_deposit)
         */
         assert_at_least_one_yocto();
+        //make sure approvals are not paused
+        self.assert_not_paused(PauseScope::Approvals);
+        //anything attached on top goes to the caller's storage balance
+        self.internal_deposit_attached_storage();
 
         //get the token object from the token ID
         let mut token = self.tokens_by_id.get(&token_id).expect("No token");
@@ -78,8 +82,8 @@ impl NonFungibleTokenCore for Contract {
         //insert the token back into the tokens_by_id collection
         self.tokens_by_id.insert(&token_id, &token);
 
-        //refund any excess storage attached by the user. If the user didn't attach enough, panic. 
-        refund_deposit(storage_used);
+        //pay for the storage out of the owner's storage balance. If it doesn't cover the required, panic. 
+        self.internal_charge_storage(&token.owner_id, storage_used);
 
         //if some message was passed into the function, we initiate a cross contract call on the
         //account we're giving access to. 
@@ -142,8 +146,8 @@ impl NonFungibleTokenCore for Contract {
             .remove(&account_id)
             .is_some()
         {
-            //refund the funds released by removing the approved_account_id to the caller of the function
-            refund_approved_account_ids_iter(predecessor_account_id, [account_id].iter());
+            //give the storage released by removing the approved_account_id back to the storage balance of the caller
+            self.internal_release_approved_account_ids_iter(&predecessor_account_id, [account_id].iter());
 
             //insert the token back into the tokens_by_id collection with the account_id removed from the approval list
             self.tokens_by_id.insert(&token_id, &token);
@@ -164,8 +168,8 @@ impl NonFungibleTokenCore for Contract {
 
         //only revoke if the approved account IDs for the token is not empty
         if !token.approved_account_ids.is_empty() {
-            //refund the approved account IDs to the caller of the function
-            refund_approved_account_ids(predecessor_account_id, &token.approved_account_ids);
+            //give the storage of the approved account IDs back to the storage balance of the caller
+            self.internal_release_approved_account_ids(&predecessor_account_id, &token.approved_account_ids);
             //clear the approved account IDs
             token.approved_account_ids.clear();
             //insert the token back into the tokens_by_id collection with the approved account IDs cleared
@@ -0,0 +1,83 @@
+use crate::*;
+
+//maximum amount of entries kept in the audit log. Once reached, the oldest entries are dropped
+pub const MAX_AUDIT_LOG_ENTRIES: u64 = 1000;
+
+//an entry of the audit log recording a privileged action
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuditEntry {
+    pub id: u64,
+    //name of the action, ex. "change_costs"
+    pub action: String,
+    //account that performed the action
+    pub actor: AccountId,
+    //JSON of the value before and after the action
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    //block timestamp in milliseconds
+    pub timestamp: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    //get the entries of the audit log using pagination. from_index is the ID of the first entry and defaults to the oldest one kept
+    pub fn get_audit_log(&self, from_index: Option<U64>, limit: Option<u64>) -> Vec<AuditEntry> {
+        let start = std::cmp::max(
+            from_index.map(|index| index.0).unwrap_or(0),
+            self.first_audit_entry_id(),
+        );
+        let end = std::cmp::min(self.next_audit_entry_id, start.saturating_add(limit.unwrap_or(50)));
+
+        (start..end)
+            .filter_map(|id| self.audit_log.get(&id))
+            .collect()
+    }
+
+    //amount of entries currently kept in the audit log
+    pub fn get_audit_log_len(&self) -> U64 {
+        U64(self.next_audit_entry_id - self.first_audit_entry_id())
+    }
+}
+
+impl Contract {
+    fn first_audit_entry_id(&self) -> u64 {
+        self.next_audit_entry_id.saturating_sub(MAX_AUDIT_LOG_ENTRIES)
+    }
+
+    //append a privileged action to the audit log, dropping the oldest entry if the log is full
+    pub(crate) fn internal_audit(&mut self, action: &str, old_value: Option<String>, new_value: Option<String>) {
+        let entry = AuditEntry {
+            id: self.next_audit_entry_id,
+            action: action.to_string(),
+            actor: env::predecessor_account_id(),
+            old_value,
+            new_value,
+            timestamp: U64(now_ms()),
+        };
+        self.audit_log.insert(&entry.id, &entry);
+        self.next_audit_entry_id += 1;
+
+        if self.next_audit_entry_id > MAX_AUDIT_LOG_ENTRIES {
+            self.audit_log.remove(&(self.next_audit_entry_id - MAX_AUDIT_LOG_ENTRIES - 1));
+        }
+    }
+
+    //log a privileged action as an admin_action event and append it to the audit log
+    pub(crate) fn internal_log_admin_action(&mut self, action: &str, old_value: Option<String>, new_value: Option<String>) {
+        let admin_action_log: EventLog = EventLog {
+            standard: OWA_STANDARD_NAME.to_string(),
+            version: OWA_EVENT_VERSION.to_string(),
+            event: EventLogVariant::AdminAction(vec![AdminActionLog {
+                action: action.to_string(),
+                actor: env::predecessor_account_id().to_string(),
+                old_value: old_value.clone(),
+                new_value: new_value.clone(),
+                timestamp: U64(now_ms()),
+            }]),
+        };
+        env::log_str(&admin_action_log.to_string());
+
+        self.internal_audit(action, old_value, new_value);
+    }
+}
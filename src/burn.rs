@@ -0,0 +1,96 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /*
+        burn a token, removing it from every collection. Can be called by the owner of the token or by the owner of the contract.
+        the storage of the token goes back to whoever paid for it and the storage of the approvals to the owner of the token
+    */
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId) {
+        //assert that the user attached exactly 1 yoctoNEAR. This is for security and so that the user will be redirected to the NEAR wallet.
+        assert_one_yocto();
+
+        let predecessor_account_id = env::predecessor_account_id();
+        let token = self.tokens_by_id.get(&token_id).expect("No token");
+        require!(
+            predecessor_account_id == token.owner_id || predecessor_account_id == self.owner_id,
+            "Only the owner of the token or of the contract can burn it"
+        );
+
+        let (storage_payer, storage_released) = self.internal_burn(&token_id, None);
+        self.internal_refund_storage(&storage_payer, storage_released);
+    }
+}
+
+impl Contract {
+    /*
+        remove a token from every collection, give the storage of its approvals back to its owner and log the burn events.
+        returns who paid for the storage of the token and how much of the rest was released, so the caller decides where it goes
+    */
+    pub(crate) fn internal_burn(&mut self, token_id: &TokenId, memo: Option<String>) -> (StoragePayer, Balance) {
+        //the tokens the token migration didn't rewrite yet have no data of their own to remove
+        require!(self.token_migration.is_none(), "Can't burn tokens while a token migration is in progress");
+
+        let token = self.tokens_by_id.get(token_id).expect("No token");
+        //tokens minted before the payers were tracked were paid by their owner
+        let storage_payer = self
+            .token_storage_payers
+            .get(token_id)
+            .unwrap_or_else(|| StoragePayer::Account(token.owner_id.clone()));
+
+        //the token no longer counts as expired in the statistics
+        if self.is_marked_expired(token_id.clone()) {
+            self.expired_subscriptions = self.expired_subscriptions.saturating_sub(1);
+        }
+
+        let initial_storage_usage = env::storage_usage();
+
+        //remove the token from every collection
+        self.internal_remove_token_from_owner(&token.owner_id, token_id);
+        self.tokens_by_id.remove(token_id);
+        self.token_storage_payers.remove(token_id);
+        let token_data = self.token_data_by_id.remove(token_id).expect("No token data");
+        self.internal_unindex_expiration(token_id, &token_data);
+        self.internal_remove_token_from_type(&token_data.type_suscription, token_id);
+        self.tokens_in_order.remove(&token_sequence(token_id));
+
+        /*
+            the storage released includes the approvals, which the owner paid out of their storage balance.
+            that part goes back to the owner and only the rest to whoever paid for the token
+        */
+        let approvals_storage: u64 = token.approved_account_ids.keys().map(bytes_for_approved_account_id).sum();
+        let storage_released = env::storage_byte_cost() * Balance::from(initial_storage_usage - env::storage_usage() - approvals_storage);
+        self.internal_release_approved_account_ids(&token.owner_id, &token.approved_account_ids);
+
+        //log the nft_burn event of NEP-171 and the subscription_burned event
+        let predecessor_account_id = env::predecessor_account_id();
+        let authorized_id = if predecessor_account_id != token.owner_id {
+            Some(predecessor_account_id.to_string())
+        } else {
+            None
+        };
+        let nft_burn_log: EventLog = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_METADATA_SPEC.to_string(),
+            event: EventLogVariant::NftBurn(vec![NftBurnLog {
+                owner_id: token.owner_id.to_string(),
+                authorized_id,
+                token_ids: vec![token_id.to_string()],
+                memo: memo.clone(),
+            }]),
+        };
+        env::log_str(&nft_burn_log.to_string());
+
+        self.internal_log_event(EventLogVariant::SubscriptionBurned(vec![SubscriptionLog {
+            owner_id: token.owner_id.to_string(),
+            token_ids: vec![token_id.to_string()],
+            type_suscription: token_data.type_suscription,
+            starts_at: token_data.starts_at.map(U64),
+            expires_at: token_data.expires_at.map(U64),
+            memo,
+        }]));
+
+        (storage_payer, storage_released)
+    }
+}
@@ -1,11 +1,13 @@
 use crate::*;
+use crate::nft_core::NonFungibleTokenCore;
+use std::ops::Bound;
 
 #[near_bindgen]
 impl Contract {
     //Query for the total supply of NFTs on the contract
     pub fn nft_total_supply(&self) -> U128 {
-        //return the length of the token metadata by ID
-        U128(self.token_metadata_by_id.len() as u128)
+        //return the length of the token data by ID plus the tokens the token migration didn't rewrite yet
+        U128((self.token_data_by_id.len() + self.token_metadata_by_id.len()) as u128)
     }
 
     //Query for nft tokens on the contract regardless of the owner using pagination
@@ -13,16 +15,20 @@ impl Contract {
         //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
-        //iterate through each token using an iterator
-        self.token_metadata_by_id.keys()
-            //skip to the index we specified in the start variable
-            .skip(start as usize) 
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize) 
-            //we'll map the token IDs which are strings into Json Tokens
-            .map(|token_id| self.nft_token(token_id.clone()).unwrap())
-            //since we turned the keys into an iterator, we need to turn it back into a vector to return
-            .collect()
+        //get the tokens by their index in the vector of keys so every page costs the same
+        self.all_tokens_page(start as u64, limit)
+    }
+
+    /*
+        Query for nft tokens on the contract regardless of the owner, in the order they were minted, starting after the token
+        passed in as the cursor (the next_cursor of the previous page). The tokens the token migration didn't rewrite yet are left out
+    */
+    pub fn nft_tokens_page(&self, cursor: Option<String>, limit: Option<u64>) -> TokenPage {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(token_sequence(&cursor)),
+            None => Bound::Included(0),
+        };
+        self.tokens_in_order_page(self.tokens_in_order.range((start, Bound::Unbounded)).map(|(_, token_id)| token_id), limit)
     }
 
     //get the total supply of NFTs for a given owner
@@ -62,15 +68,135 @@ impl Contract {
         //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
-        //iterate through the keys vector
+        //get the tokens by their index in the vector of the set so every page costs the same
+        self.tokens_page(tokens.as_vector(), start as u64, limit)
+    }
+
+    //Query for the tokens for an owner in the order they were minted, starting after the token passed in as the cursor
+    pub fn nft_tokens_for_owner_page(
+        &self,
+        account_id: AccountId,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> TokenPage {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded((account_id.clone(), token_sequence(&cursor))),
+            None => Bound::Included((account_id.clone(), 0)),
+        };
+        let tokens_for_owner = self.tokens_per_owner_in_order
+            .range((start, Bound::Included((account_id.clone(), u64::MAX))))
+            .map(|(_, token_id)| token_id);
+        self.tokens_in_order_page(tokens_for_owner, limit)
+    }
+
+    //get the total supply of NFTs of a subscription type
+    pub fn nft_supply_for_type(&self, type_suscription: String) -> U128 {
+        self.tokens_per_type
+            .get(&type_suscription)
+            .map(|tokens_for_type_set| U128(tokens_for_type_set.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    //Query for all the tokens of a subscription type
+    pub fn nft_tokens_for_type(
+        &self,
+        type_suscription: String,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken> {
+        //get the set of tokens for the passed in type, if there is none we return an empty vector
+        let tokens = if let Some(tokens_for_type_set) = self.tokens_per_type.get(&type_suscription) {
+            tokens_for_type_set
+        } else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.tokens_page(tokens.as_vector(), start as u64, limit)
+    }
+
+    //Query for the tokens of a subscription type that an owner has
+    pub fn nft_tokens_for_owner_by_type(
+        &self,
+        account_id: AccountId,
+        type_suscription: String,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken> {
+        let tokens = if let Some(tokens_for_owner_set) = self.tokens_per_owner.get(&account_id) {
+            tokens_for_owner_set
+        } else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
         tokens.iter()
-            //skip to the index we specified in the start variable
-            .skip(start as usize) 
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize) 
-            //we'll map the token IDs which are strings into Json Tokens
-            .map(|token_id| self.nft_token(token_id.clone()).unwrap())
-            //since we turned the keys into an iterator, we need to turn it back into a vector to return
+            //keep only the tokens of the passed in type
+            .filter(|token_id| self.plan_for_token(token_id.clone()).as_deref() == Some(type_suscription.as_str()))
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|token_id| self.nft_token(token_id).unwrap())
             .collect()
     }
 }
+
+impl Contract {
+    /*
+        get a page of tokens from a vector of token IDs, starting from the passed in index.
+        every token is read by its index so the cost doesn't depend on where the page starts
+    */
+    fn tokens_page(&self, token_ids: &Vector<TokenId>, start: u64, limit: Option<u64>) -> Vec<JsonToken> {
+        self.tokens_page_by_index(token_ids.len(), |index| token_ids.get(index).unwrap(), start, limit)
+    }
+
+    //get a page of every token of the contract: the ones stored with their data followed by the ones the token migration didn't rewrite yet
+    fn all_tokens_page(&self, start: u64, limit: Option<u64>) -> Vec<JsonToken> {
+        let token_data_ids = self.token_data_by_id.keys_as_vector();
+        let token_metadata_ids = self.token_metadata_by_id.keys_as_vector();
+
+        self.tokens_page_by_index(
+            token_data_ids.len() + token_metadata_ids.len(),
+            |index| {
+                if index < token_data_ids.len() {
+                    token_data_ids.get(index).unwrap()
+                } else {
+                    token_metadata_ids.get(index - token_data_ids.len()).unwrap()
+                }
+            },
+            start,
+            limit,
+        )
+    }
+
+    fn tokens_page_by_index(&self, len: u64, token_id_at: impl Fn(u64) -> TokenId, start: u64, limit: Option<u64>) -> Vec<JsonToken> {
+        //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+        let end = std::cmp::min(len, start.saturating_add(limit.unwrap_or(50)));
+
+        (start..end)
+            .map(|index| self.nft_token(token_id_at(index)).unwrap())
+            .collect()
+    }
+
+    /*
+        get a page of tokens out of the token IDs ordered by when they were minted, which start after the cursor.
+        the cursor of the next page is the last token of this one, so burns, sweeps and transfers don't move the pages
+        that are left and the tokens minted between pages show up at the end
+    */
+    fn tokens_in_order_page(&self, mut token_ids: impl Iterator<Item = TokenId>, limit: Option<u64>) -> TokenPage {
+        let tokens: Vec<JsonToken> = token_ids
+            .by_ref()
+            .take(limit.unwrap_or(50) as usize)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect();
+
+        TokenPage {
+            //there's a next page if there is another token after the last one of this page
+            next_cursor: if token_ids.next().is_some() { tokens.last().map(|token| token.token_id.clone()) } else { None },
+            tokens,
+        }
+    }
+}
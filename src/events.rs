@@ -1,9 +1,13 @@
 use std::fmt;
 
+use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 
 /// Enum that represents the data type of the EventLog.
-/// The enum can either be an NftMint or an NftTransfer.
+/// The NEP-171 events (NftMint, NftTransfer, NftBurn) are logged under the nep171 standard,
+/// the rest are logged under the contract's own owa_subscriptions standard.
+/// The subscription events (PaymentReceived to SubscriptionBurned) are enough to rebuild
+/// the subscription history of every account.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "event", content = "data")]
 #[serde(rename_all = "snake_case")]
@@ -12,6 +16,27 @@ use near_sdk::serde::{Deserialize, Serialize};
 pub enum EventLogVariant {
     NftMint(Vec<NftMintLog>),
     NftTransfer(Vec<NftTransferLog>),
+    NftBurn(Vec<NftBurnLog>),
+    OwnershipProposed(Vec<OwnershipLog>),
+    OwnershipProposalCancelled(Vec<OwnershipLog>),
+    OwnershipTransferred(Vec<OwnershipLog>),
+    UpgradeStaged(Vec<UpgradeLog>),
+    UpgradeCancelled(Vec<UpgradeLog>),
+    UpgradeDeployed(Vec<UpgradeLog>),
+    ContractPaused(Vec<PauseLog>),
+    ContractUnpaused(Vec<PauseLog>),
+    AdminAction(Vec<AdminActionLog>),
+    PaymentReceived(Vec<PaymentLog>),
+    CreditGranted(Vec<CreditLog>),
+    SubscriptionMinted(Vec<SubscriptionLog>),
+    SubscriptionRenewed(Vec<SubscriptionLog>),
+    SubscriptionUpgraded(Vec<SubscriptionUpgradeLog>),
+    SubscriptionPaused(Vec<SubscriptionPauseLog>),
+    SubscriptionResumed(Vec<SubscriptionPauseLog>),
+    SubscriptionExpired(Vec<SubscriptionLog>),
+    SubscriptionExpiringSoon(Vec<SubscriptionLog>),
+    SubscriptionRefunded(Vec<RefundLog>),
+    SubscriptionBurned(Vec<SubscriptionLog>),
 }
 
 /// Interface to capture data about an event
@@ -78,6 +103,200 @@ pub struct NftTransferLog {
     pub memo: Option<String>,
 }
 
+/// An event log to capture token burning
+///
+/// Arguments
+/// * `owner_id`: owner of the NFT that was burned, "owner.near"
+/// * `authorized_id`: optional account that burned the token on behalf of the owner
+/// * `token_ids`: ["1", "abc"]
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<String>,
+
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture ownership transfers
+///
+/// Arguments
+/// * `old_owner_id`: "owner.near"
+/// * `new_owner_id`: "dao.near"
+/// * `accept_after`: optional timestamp in milliseconds from which the transfer can be accepted
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipLog {
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_after: Option<U64>,
+}
+
+/// An event log to capture the upgrades of the contract code
+///
+/// Arguments
+/// * `code_hash`: base58 sha256 of the wasm
+/// * `deploy_after`: optional timestamp in milliseconds from which the upgrade can be deployed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeLog {
+    pub code_hash: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deploy_after: Option<U64>,
+}
+
+/// An event log to capture the contract being paused or unpaused
+///
+/// Arguments
+/// * `account_id`: "pauser.near"
+/// * `scopes`: ["payments", "transfers"]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseLog {
+    pub account_id: String,
+    pub scopes: Vec<String>,
+}
+
+/// An event log to capture a privileged action, also kept in the audit log
+///
+/// Arguments
+/// * `action`: "change_costs"
+/// * `actor`: "admin.near"
+/// * `old_value`: optional JSON of the value before the action
+/// * `new_value`: optional JSON of the value after the action
+/// * `timestamp`: block timestamp in milliseconds
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminActionLog {
+    pub action: String,
+    pub actor: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<String>,
+
+    pub timestamp: U64,
+}
+
+/// An event log to capture a USDT.e payment
+///
+/// Arguments
+/// * `account_id`: "buyer.near"
+/// * `amount`: "10000000"
+/// * `type_suscription`: "one_month"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PaymentLog {
+    pub account_id: String,
+    pub amount: U128,
+    pub type_suscription: String,
+}
+
+/// An event log to capture a subscription credited to an account, to be minted later
+///
+/// Arguments
+/// * `account_id`: "buyer.near"
+/// * `type_suscription`: "one_month"
+/// * `credits`: subscriptions of the type the account can mint after the credit
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CreditLog {
+    pub account_id: String,
+    pub type_suscription: String,
+    pub credits: u64,
+}
+
+/// An event log to capture a change in the state of a subscription
+///
+/// Arguments
+/// * `owner_id`: "buyer.near"
+/// * `token_ids`: ["1", "2"]
+/// * `type_suscription`: "one_month"
+/// * `starts_at`: optional timestamp in milliseconds at which the subscription starts
+/// * `expires_at`: optional timestamp in milliseconds at which the subscription expires, none if permanent
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionLog {
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+    pub type_suscription: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture a subscription minted while the owner has an active subscription of another type
+///
+/// Arguments
+/// * `owner_id`: "buyer.near"
+/// * `token_ids`: ["3"]
+/// * `old_type_suscription`: "one_month"
+/// * `type_suscription`: "one_year"
+/// * `starts_at`: optional timestamp in milliseconds at which the new subscription starts
+/// * `expires_at`: optional timestamp in milliseconds at which the new subscription expires, none if permanent
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionUpgradeLog {
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+    pub old_type_suscription: String,
+    pub type_suscription: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<U64>,
+}
+
+/// An event log to capture the subscriptions of some types being paused or resumed
+///
+/// Arguments
+/// * `type_suscriptions`: ["one_month", "one_year"]
+/// * `scopes`: what is paused or resumed: ["payments", "minting", "transfers", "approvals"]
+/// * `until`: optional timestamp in milliseconds at which the pause ends by itself
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionPauseLog {
+    pub type_suscriptions: Vec<String>,
+    pub scopes: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<U64>,
+}
+
+/// An event log to capture a USDT.e payment sent back to the sender
+///
+/// Arguments
+/// * `account_id`: "buyer.near"
+/// * `amount`: "10000000"
+/// * `type_suscription`: optional subscription type of the amount, none if it doesn't match any
+/// * `reason`: "The contract is paused for payments"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RefundLog {
+    pub account_id: String,
+    pub amount: U128,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_suscription: Option<String>,
+    pub reason: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,4 +354,37 @@ mod tests {
         };
         assert_eq!(expected, log.to_string());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn owa_format_ownership_proposed() {
+        let expected = r#"EVENT_JSON:{"standard":"owa_subscriptions","version":"1.0.0","event":"ownership_proposed","data":[{"old_owner_id":"owner.near","new_owner_id":"dao.near","accept_after":"100"}]}"#;
+        let log = EventLog {
+            standard: "owa_subscriptions".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::OwnershipProposed(vec![OwnershipLog {
+                old_owner_id: "owner.near".to_string(),
+                new_owner_id: "dao.near".to_string(),
+                accept_after: Some(U64(100)),
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+
+    #[test]
+    fn owa_format_subscription_minted() {
+        let expected = r#"EVENT_JSON:{"standard":"owa_subscriptions","version":"1.0.0","event":"subscription_minted","data":[{"owner_id":"buyer.near","token_ids":["0"],"type_suscription":"one_month","starts_at":"10","expires_at":"20"}]}"#;
+        let log = EventLog {
+            standard: "owa_subscriptions".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::SubscriptionMinted(vec![SubscriptionLog {
+                owner_id: "buyer.near".to_string(),
+                token_ids: vec!["0".to_string()],
+                type_suscription: "one_month".to_string(),
+                starts_at: Some(U64(10)),
+                expires_at: Some(U64(20)),
+                memo: None,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::*;
+use crate::nft_core::NonFungibleTokenCore;
+use std::collections::HashSet;
+
+//maximum amount of tokens process_expirations goes through in a single call, so the events fit in the logs
+pub const MAX_EXPIRATIONS_PER_CALL: u64 = 50;
+
+//a position in the expiry index
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpiryKey {
+    pub expires_at: U64,
+    pub token_id: TokenId,
+}
+
+//how far the expirations have been processed
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpirationProgress {
+    //last token marked as expired
+    pub expired_until: Option<ExpiryKey>,
+    //last token for which the expiring soon notice was logged
+    pub expiring_soon_until: Option<ExpiryKey>,
+    //how long (in milliseconds) before the expiration the notice is logged, 0 if disabled
+    pub expiring_soon_notice: U64,
+}
+
+//what a call to process_expirations did
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpirationResult {
+    pub expired: u64,
+    pub expiring_soon: u64,
+    //true if there's nothing left to process at the current time
+    pub done: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        go through the expiry index marking the tokens whose expiration has passed as expired and logging subscription_expired,
+        then log subscription_expiring_soon for the tokens that expire within the notice period. Anyone can call it.
+        the progress is kept in two cursors so each call starts where the last one stopped
+    */
+    pub fn process_expirations(&mut self, limit: Option<u64>) -> ExpirationResult {
+        let now = now_ms();
+        let limit = std::cmp::min(limit.unwrap_or(MAX_EXPIRATIONS_PER_CALL), MAX_EXPIRATIONS_PER_CALL);
+
+        //tokens whose expiration has passed
+        let mut expired = vec![];
+        while (expired.len() as u64) < limit {
+            match self.next_expiry_key(&self.expiration_cursor) {
+                Some(key) if key.0 <= now => {
+                    expired.push(self.internal_subscription_log(&key));
+                    self.expiration_cursor = Some(key);
+                }
+                _ => break,
+            }
+        }
+
+        //tokens that expire within the notice period. The ones that already expired don't get a notice
+        let mut expiring_soon = vec![];
+        let mut checked = expired.len() as u64;
+        if self.expiring_soon_notice > 0 {
+            let horizon = now + self.expiring_soon_notice;
+            while checked < limit {
+                match self.next_expiry_key(&self.expiring_soon_cursor) {
+                    Some(key) if key.0 <= horizon => {
+                        if key.0 > now {
+                            expiring_soon.push(self.internal_subscription_log(&key));
+                        }
+                        self.expiring_soon_cursor = Some(key);
+                        checked += 1;
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        let result = ExpirationResult {
+            expired: expired.len() as u64,
+            expiring_soon: expiring_soon.len() as u64,
+            done: checked < limit,
+        };
+        self.expired_subscriptions += result.expired;
+        if !expired.is_empty() {
+            self.internal_log_event(EventLogVariant::SubscriptionExpired(expired));
+        }
+        if !expiring_soon.is_empty() {
+            self.internal_log_event(EventLogVariant::SubscriptionExpiringSoon(expiring_soon));
+        }
+
+        result
+    }
+
+    //set how long (in milliseconds) before the expiration the expiring soon notice is logged. 0 disables it
+    pub fn set_expiring_soon_notice(&mut self, notice: U64) {
+        self.assert_role(Role::Admin);
+        let old_notice = U64(self.expiring_soon_notice);
+        self.expiring_soon_notice = notice.0;
+        self.internal_log_admin_action(
+            "set_expiring_soon_notice",
+            Some(json!(old_notice).to_string()),
+            Some(json!(notice).to_string()),
+        );
+    }
+
+    pub fn get_expiration_progress(&self) -> ExpirationProgress {
+        let to_expiry_key = |key: &(u64, TokenId)| ExpiryKey {
+            expires_at: U64(key.0),
+            token_id: key.1.clone(),
+        };
+
+        ExpirationProgress {
+            expired_until: self.expiration_cursor.as_ref().map(to_expiry_key),
+            expiring_soon_until: self.expiring_soon_cursor.as_ref().map(to_expiry_key),
+            expiring_soon_notice: U64(self.expiring_soon_notice),
+        }
+    }
+
+    /*
+        get the tokens that expire from the timestamp from (inclusive) to the timestamp to (exclusive), in milliseconds, ordered by expiration.
+        the next page starts after the key of the last token of the previous one, so every page costs the same
+    */
+    pub fn tokens_expiring_between(&self, from: U64, to: U64, after: Option<ExpiryKey>, limit: Option<u64>) -> Vec<JsonToken> {
+        //without a cursor, or one from before the range, the page starts at the from timestamp
+        let start = match after {
+            Some(after) if after.expires_at.0 >= from.0 => (after.expires_at.0, after.token_id),
+            _ => (from.0, String::new()),
+        };
+
+        self.expiring_after(start, to.0)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(_, token_id)| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    //get the accounts that own tokens expiring from the timestamp from (inclusive) to the timestamp to (exclusive), in milliseconds, ordered by their first expiration
+    pub fn accounts_expiring_between(&self, from: U64, to: U64, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        let end = start as usize + limit.unwrap_or(50) as usize;
+
+        //walk the range only until the accounts of the page are found, keeping the ones already seen in a set
+        let mut seen: HashSet<AccountId> = HashSet::new();
+        let mut accounts: Vec<AccountId> = vec![];
+        for (_, token_id) in self.expiring_between(from.0, to.0) {
+            if accounts.len() >= end {
+                break;
+            }
+            let owner_id = self.tokens_by_id.get(&token_id).unwrap().owner_id;
+            if seen.insert(owner_id.clone()) {
+                accounts.push(owner_id);
+            }
+        }
+
+        accounts.into_iter().skip(start as usize).collect()
+    }
+
+    //check if a token was already marked as expired by process_expirations
+    pub fn is_marked_expired(&self, token_id: TokenId) -> bool {
+        let expires_at = self.internal_token_data(&token_id).and_then(|token_data| token_data.expires_at);
+        match (expires_at, &self.expiration_cursor) {
+            (Some(expires_at), Some(cursor)) => (expires_at, token_id) <= *cursor,
+            _ => false,
+        }
+    }
+}
+
+impl Contract {
+    //get the key that comes after the passed in cursor in the expiry index
+    fn next_expiry_key(&self, cursor: &Option<(u64, TokenId)>) -> Option<(u64, TokenId)> {
+        match cursor {
+            Some(cursor) => self.expiry_index.higher(cursor),
+            None => self.expiry_index.min(),
+        }
+    }
+
+    //go through the keys of the expiry index that expire from the timestamp from (inclusive) to the timestamp to (exclusive)
+    fn expiring_between(&self, from: u64, to: u64) -> impl Iterator<Item = (u64, TokenId)> + '_ {
+        //no token ID is empty, so every key with the from timestamp comes after this one
+        self.expiring_after((from, String::new()), to)
+    }
+
+    //go through the keys of the expiry index after the passed in one that expire before the timestamp to (exclusive)
+    fn expiring_after(&self, start: (u64, TokenId), to: u64) -> impl Iterator<Item = (u64, TokenId)> + '_ {
+        self.expiry_index
+            .iter_from(start)
+            .map(|(key, _)| key)
+            .take_while(move |key| key.0 < to)
+    }
+
+    fn internal_subscription_log(&self, key: &(u64, TokenId)) -> SubscriptionLog {
+        let token = self.tokens_by_id.get(&key.1).expect("No token");
+        let token_data = self.internal_token_data(&key.1).expect("No token data");
+        SubscriptionLog {
+            owner_id: token.owner_id.to_string(),
+            token_ids: vec![key.1.clone()],
+            type_suscription: token_data.type_suscription,
+            starts_at: token_data.starts_at.map(U64),
+            expires_at: Some(U64(key.0)),
+            memo: None,
+        }
+    }
+
+    //add a token to the expiry index if it expires
+    pub(crate) fn internal_index_expiration(&mut self, token_id: &TokenId, token_data: &TokenData) {
+        if let Some(expires_at) = token_data.expires_at {
+            self.expiry_index.insert(&(expires_at, token_id.clone()), &());
+        }
+    }
+
+    //remove a token from the expiry index
+    pub(crate) fn internal_unindex_expiration(&mut self, token_id: &TokenId, token_data: &TokenData) {
+        if let Some(expires_at) = token_data.expires_at {
+            self.expiry_index.remove(&(expires_at, token_id.clone()));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::*;
+
+//how the configuration changes (costs, USDT contract and the governance itself) are approved
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum GovernanceMode {
+    //the changes take effect as soon as an account with the right role calls the method
+    Direct,
+    //the changes only take effect when called by a Sputnik DAO, as the result of one of its proposals
+    SputnikDao { dao_id: AccountId },
+    //the calls become proposals that take effect once a quorum of the council approves them and the timelock (in milliseconds) passes
+    Council { members: Vec<AccountId>, quorum: u32, timelock: U64 },
+}
+
+//a change of configuration that goes through the governance
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConfigChange {
+    Costs {
+        one_month_cost: U128,
+        six_months_cost: U128,
+        one_year_cost: U128,
+        permanent_cost: U128,
+    },
+    UsdtContract { new_contract: String },
+    Governance { mode: GovernanceMode },
+}
+
+//a configuration change proposed to the council
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigProposal {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    //block timestamp (in milliseconds) at which the proposal reached the quorum
+    pub approved_at: Option<U64>,
+    pub executed: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_governance_mode(&self) -> GovernanceMode {
+        self.governance.clone()
+    }
+
+    //change the governance mode. It goes through the current governance like any other configuration change
+    pub fn set_governance_mode(&mut self, mode: GovernanceMode) -> Option<u64> {
+        if let GovernanceMode::Council { members, quorum, .. } = &mode {
+            require!(
+                *quorum > 0 && *quorum as usize <= members.len(),
+                "The quorum must be between 1 and the number of council members"
+            );
+        }
+
+        self.internal_govern(ConfigChange::Governance { mode }, Role::Admin)
+    }
+
+    //approve a proposal as a member of the council
+    pub fn approve_config_change(&mut self, proposal_id: u64) -> ConfigProposal {
+        let (members, quorum) = self.assert_council_member();
+        let mut proposal = self.config_proposals.get(&proposal_id).expect("No proposal");
+        require!(!proposal.executed, "The proposal was already executed");
+
+        let account_id = env::predecessor_account_id();
+        require!(!proposal.approvals.contains(&account_id), "The proposal was already approved by this account");
+        proposal.approvals.push(account_id);
+        self.internal_log_admin_action("approve_config_change", None, Some(json!(proposal_id).to_string()));
+
+        //the timelock starts counting once the quorum is reached
+        let approvals = proposal.approvals.iter().filter(|account_id| members.contains(account_id)).count();
+        if proposal.approved_at.is_none() && approvals >= quorum as usize {
+            proposal.approved_at = Some(U64(now_ms()));
+        }
+
+        self.config_proposals.insert(&proposal_id, &proposal);
+        proposal
+    }
+
+    //apply an approved proposal once its timelock has passed
+    pub fn execute_config_change(&mut self, proposal_id: u64) -> ConfigProposal {
+        self.assert_council_member();
+        let timelock = match &self.governance {
+            GovernanceMode::Council { timelock, .. } => timelock.0,
+            _ => env::panic_str("The contract is not governed by a council"),
+        };
+        let mut proposal = self.config_proposals.get(&proposal_id).expect("No proposal");
+        require!(!proposal.executed, "The proposal was already executed");
+        let approved_at = proposal.approved_at.expect("The proposal has not reached the quorum");
+        require!(
+            now_ms() >= approved_at.0 + timelock,
+            "The proposal can't be executed yet"
+        );
+
+        proposal.executed = true;
+        self.config_proposals.insert(&proposal_id, &proposal);
+        self.internal_apply_config_change(proposal.change.clone());
+        proposal
+    }
+
+    pub fn get_config_proposal(&self, proposal_id: u64) -> Option<ConfigProposal> {
+        self.config_proposals.get(&proposal_id)
+    }
+
+    //get the proposals using pagination
+    pub fn get_config_proposals(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<ConfigProposal> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.config_proposals.values()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    /*
+        route a configuration change through the current governance mode:
+        applied right away when called by an account with the passed in role (Direct) or by the DAO (SputnikDao),
+        or turned into a proposal of the caller (Council). Returns the ID of the proposal if one was created
+    */
+    pub(crate) fn internal_govern(&mut self, change: ConfigChange, role: Role) -> Option<u64> {
+        match self.governance.clone() {
+            GovernanceMode::Direct => {
+                self.assert_role(role);
+                self.internal_apply_config_change(change);
+                None
+            }
+            GovernanceMode::SputnikDao { dao_id } => {
+                require!(
+                    env::predecessor_account_id() == dao_id,
+                    "Configuration changes can only be made by the DAO"
+                );
+                self.internal_apply_config_change(change);
+                None
+            }
+            GovernanceMode::Council { quorum, .. } => {
+                self.assert_council_member();
+                let proposal = ConfigProposal {
+                    id: self.next_config_proposal_id,
+                    change,
+                    proposer: env::predecessor_account_id(),
+                    //the proposer approves its own proposal
+                    approvals: vec![env::predecessor_account_id()],
+                    approved_at: if quorum <= 1 { Some(U64(now_ms())) } else { None },
+                    executed: false,
+                };
+                self.config_proposals.insert(&proposal.id, &proposal);
+                self.next_config_proposal_id += 1;
+                self.internal_log_admin_action("propose_config_change", None, Some(json!(proposal).to_string()));
+
+                log!("Configuration change proposed with ID {}", proposal.id);
+                Some(proposal.id)
+            }
+        }
+    }
+
+    //apply a configuration change and log it with the value it replaces
+    fn internal_apply_config_change(&mut self, change: ConfigChange) {
+        let (action, old_value, new_value) = match change {
+            ConfigChange::Costs { one_month_cost, six_months_cost, one_year_cost, permanent_cost } => {
+                let old_costs = json!(self.show_costs());
+                self.one_month_cost = one_month_cost;
+                self.six_months_cost = six_months_cost;
+                self.one_year_cost = one_year_cost;
+                self.permanent_cost = permanent_cost;
+                ("change_costs", old_costs, json!(self.show_costs()))
+            }
+            ConfigChange::UsdtContract { new_contract } => {
+                let old_contract = std::mem::replace(&mut self.usdt_contract, new_contract);
+                ("change_usdt_contract", json!(old_contract), json!(self.usdt_contract))
+            }
+            ConfigChange::Governance { mode } => {
+                let old_mode = std::mem::replace(&mut self.governance, mode);
+                ("set_governance_mode", json!(old_mode), json!(self.governance))
+            }
+        };
+
+        self.internal_log_admin_action(action, Some(old_value.to_string()), Some(new_value.to_string()));
+    }
+
+    //make sure the predecessor is a member of the council and return the members and the quorum
+    fn assert_council_member(&self) -> (Vec<AccountId>, u32) {
+        if let GovernanceMode::Council { members, quorum, .. } = &self.governance {
+            require!(
+                members.contains(&env::predecessor_account_id()),
+                "Only council members can call this method"
+            );
+            (members.clone(), *quorum)
+        } else {
+            env::panic_str("The contract is not governed by a council")
+        }
+    }
+}
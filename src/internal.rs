@@ -13,29 +13,6 @@ pub(crate) fn bytes_for_approved_account_id(account_id: &AccountId) -> u64 {
     account_id.as_str().len() as u64 + 4 + size_of::<u64>() as u64
 }
 
-//refund the storage taken up by passed in approved account IDs and send the funds to the passed in account ID. 
-pub(crate) fn refund_approved_account_ids_iter<'a, I>(
-    account_id: AccountId,
-    approved_account_ids: I, //the approved account IDs must be passed in as an iterator
-) -> Promise
-where
-    I: Iterator<Item = &'a AccountId>,
-{
-    //get the storage total by going through and summing all the bytes for each approved account IDs
-    let storage_released: u64 = approved_account_ids.map(bytes_for_approved_account_id).sum();
-    //transfer the account the storage that is released
-    Promise::new(account_id).transfer(Balance::from(storage_released) * env::storage_byte_cost())
-}
-
-//refund a map of approved account IDs and send the funds to the passed in account ID
-pub(crate) fn refund_approved_account_ids(
-    account_id: AccountId,
-    approved_account_ids: &HashMap<AccountId, u64>,
-) -> Promise {
-    //call the refund_approved_account_ids_iter with the approved account IDs as keys
-    refund_approved_account_ids_iter(account_id, approved_account_ids.keys())
-}
-
 //used to generate a unique prefix in our storage collections (this is to avoid data collisions)
 pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
     //get the default hash
@@ -45,6 +22,15 @@ pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
     hash
 }
 
+//used to generate a unique prefix for the set of tokens of a subscription type
+pub(crate) fn hash_token_type(token_type: &str) -> CryptoHash {
+    //get the default hash
+    let mut hash = CryptoHash::default();
+    //we hash the token type and return it
+    hash.copy_from_slice(&env::sha256(token_type.as_bytes()));
+    hash
+}
+
 //used to make sure the user attached exactly 1 yoctoNEAR
 pub(crate) fn assert_one_yocto() {
     assert_eq!(
@@ -62,30 +48,25 @@ pub(crate) fn assert_at_least_one_yocto() {
     )
 }
 
-//refund the initial deposit based on the amount of storage that was used up
-pub(crate) fn refund_deposit(storage_used: u64) {
-    //get how much it would cost to store the information
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
-    //get the attached deposit
-    let attached_deposit = env::attached_deposit();
-
-    //make sure that the attached deposit is greater than or equal to the required cost
-    assert!(
-        required_cost <= attached_deposit,
-        "Must attach {} yoctoNEAR to cover storage",
-        required_cost,
-    );
-
-    //get the refund amount from the attached deposit - required cost
-    let refund = attached_deposit - required_cost;
+impl Contract {
+    /*
+        give back the storage taken up by the passed in approved account IDs to the account that paid for it out of its storage balance.
+        the approved account IDs must be passed in as an iterator
+    */
+    pub(crate) fn internal_release_approved_account_ids_iter<'a, I>(&mut self, account_id: &AccountId, approved_account_ids: I)
+    where
+        I: Iterator<Item = &'a AccountId>,
+    {
+        //get the storage total by going through and summing all the bytes for each approved account IDs
+        let storage_released: u64 = approved_account_ids.map(bytes_for_approved_account_id).sum();
+        self.internal_release_storage(account_id, Balance::from(storage_released) * env::storage_byte_cost());
+    }
 
-    //if the refund is greater than 1 yocto NEAR, we refund the predecessor that amount
-    if refund > 1 {
-        Promise::new(env::predecessor_account_id()).transfer(refund);
+    //give back the storage taken up by a map of approved account IDs to the passed in account ID
+    pub(crate) fn internal_release_approved_account_ids(&mut self, account_id: &AccountId, approved_account_ids: &HashMap<AccountId, u64>) {
+        self.internal_release_approved_account_ids_iter(account_id, approved_account_ids.keys())
     }
-}
 
-impl Contract {
     //add a token to the set of tokens an owner has
     pub(crate) fn internal_add_token_to_owner(
         &mut self,
@@ -110,6 +91,46 @@ impl Contract {
 
         //we insert that set for the given account ID. 
         self.tokens_per_owner.insert(account_id, &tokens_set);
+        self.tokens_per_owner_in_order.insert(&(account_id.clone(), token_sequence(token_id)), token_id);
+    }
+
+    //add a token to the set of tokens of a subscription type
+    pub(crate) fn internal_add_token_to_type(
+        &mut self,
+        token_type: &str,
+        token_id: &TokenId,
+    ) {
+        //get the set of tokens for the given type, creating it if it's the first one
+        let mut tokens_set = self.tokens_per_type.get(&token_type.to_string()).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::TokensPerTypeInner {
+                    token_type_hash: hash_token_type(token_type),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+
+        tokens_set.insert(token_id);
+        self.tokens_per_type.insert(&token_type.to_string(), &tokens_set);
+    }
+
+    //remove a token from the set of tokens of a subscription type
+    pub(crate) fn internal_remove_token_from_type(
+        &mut self,
+        token_type: &str,
+        token_id: &TokenId,
+    ) {
+        if let Some(mut tokens_set) = self.tokens_per_type.get(&token_type.to_string()) {
+            tokens_set.remove(token_id);
+
+            //if the set is now empty, we remove the type from the tokens_per_type collection
+            if tokens_set.is_empty() {
+                self.tokens_per_type.remove(&token_type.to_string());
+            } else {
+                self.tokens_per_type.insert(&token_type.to_string(), &tokens_set);
+            }
+        }
     }
 
     //remove a token from an owner (internal method and can't be called directly via CLI).
@@ -127,6 +148,7 @@ impl Contract {
 
         //we remove the the token_id from the set of tokens
         tokens_set.remove(token_id);
+        self.tokens_per_owner_in_order.remove(&(account_id.clone(), token_sequence(token_id)));
 
         //if the token set is now empty, we remove the owner from the tokens_per_owner collection
         if tokens_set.is_empty() {
@@ -147,9 +169,17 @@ impl Contract {
         approval_id: Option<u64>,
         memo: Option<String>,
     ) -> Token {
+        //make sure transfers are not paused
+        self.assert_not_paused(PauseScope::Transfers);
+
         //get the token object by passing in the token_id
         let token = self.tokens_by_id.get(token_id).expect("No token");
 
+        //make sure the subscription type of the token is not locked. This covers nft_transfer, nft_transfer_call and nft_transfer_payout
+        if let Some(type_suscription) = self.plan_for_token(token_id.clone()) {
+            self.assert_type_not_locked(&type_suscription);
+        }
+
         //if the sender doesn't equal the owner, we check if the sender is in the approval list
 		if sender_id != &token.owner_id {
 			//if the token's approved account IDs doesn't contain the sender, we panic
@@ -237,4 +267,76 @@ impl Contract {
         //return the previous token object that was transferred.
         token
     }
+
+    //log an event of the contract (subscription lifecycle, ownership, upgrades, pauses) under the owa_subscriptions standard
+    pub(crate) fn internal_log_event(&self, event: EventLogVariant) {
+        let event_log: EventLog = EventLog {
+            standard: OWA_STANDARD_NAME.to_string(),
+            version: OWA_EVENT_VERSION.to_string(),
+            event,
+        };
+
+        env::log_str(&event_log.to_string());
+    }
+
+    //log the nft_mint event of NEP-171 and the subscription_minted event of a new token
+    pub(crate) fn internal_log_subscription_minted(&self, owner_id: &AccountId, token_id: &TokenId, token_data: &TokenData) {
+        let nft_mint_log: EventLog = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_METADATA_SPEC.to_string(),
+            event: EventLogVariant::NftMint(vec![NftMintLog {
+                owner_id: owner_id.to_string(),
+                token_ids: vec![token_id.to_string()],
+                memo: None,
+            }]),
+        };
+        env::log_str(&nft_mint_log.to_string());
+
+        self.internal_log_event(EventLogVariant::SubscriptionMinted(vec![SubscriptionLog {
+            owner_id: owner_id.to_string(),
+            token_ids: vec![token_id.to_string()],
+            type_suscription: token_data.type_suscription.clone(),
+            starts_at: token_data.starts_at.map(U64),
+            expires_at: token_data.expires_at.map(U64),
+            memo: None,
+        }]));
+
+        //a mint while the owner still has an active subscription renews it, or upgrades it if the type changes
+        if let Some(previous_type) = self.internal_active_subscription_type(owner_id, token_id) {
+            if previous_type == token_data.type_suscription {
+                self.internal_log_event(EventLogVariant::SubscriptionRenewed(vec![SubscriptionLog {
+                    owner_id: owner_id.to_string(),
+                    token_ids: vec![token_id.to_string()],
+                    type_suscription: token_data.type_suscription.clone(),
+                    starts_at: token_data.starts_at.map(U64),
+                    expires_at: token_data.expires_at.map(U64),
+                    memo: None,
+                }]));
+            } else {
+                self.internal_log_event(EventLogVariant::SubscriptionUpgraded(vec![SubscriptionUpgradeLog {
+                    owner_id: owner_id.to_string(),
+                    token_ids: vec![token_id.to_string()],
+                    old_type_suscription: previous_type,
+                    type_suscription: token_data.type_suscription.clone(),
+                    starts_at: token_data.starts_at.map(U64),
+                    expires_at: token_data.expires_at.map(U64),
+                }]));
+            }
+        }
+    }
+
+    //type of the newest active subscription of the owner other than the passed in token, looking at most at the last MAX_OVERVIEW_TOKENS tokens
+    fn internal_active_subscription_type(&self, owner_id: &AccountId, token_id: &TokenId) -> Option<String> {
+        let now = now_ms();
+        let tokens = self.tokens_per_owner.get(owner_id)?;
+        tokens
+            .as_vector()
+            .iter()
+            .rev()
+            .filter(|other_id| other_id != token_id)
+            .take(MAX_OVERVIEW_TOKENS)
+            .filter_map(|other_id| self.token_data_by_id.get(&other_id))
+            .find(|token_data| token_data.expires_at.map(|expires_at| expires_at > now).unwrap_or(true))
+            .map(|token_data| token_data.type_suscription)
+    }
 } 
\ No newline at end of file
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
 use near_sdk::{
-    env, log, near_bindgen, AccountId, Balance, CryptoHash, PanicOnDefault, Promise, PromiseOrValue,
+    env, log, near_bindgen, require, AccountId, Balance, CryptoHash, PanicOnDefault, Promise, PromiseOrValue,
 };
 
 use crate::internal::*;
@@ -15,6 +16,21 @@ pub use crate::approval::*;
 pub use crate::royalty::*;
 pub use crate::events::*;
 pub use crate::migrate::*;
+pub use crate::storage::*;
+pub use crate::sponsorship::*;
+pub use crate::roles::*;
+pub use crate::ownership::*;
+pub use crate::pause::*;
+pub use crate::governance::*;
+pub use crate::audit::*;
+pub use crate::sweep::*;
+pub use crate::expiration::*;
+pub use crate::locks::*;
+pub use crate::account::*;
+pub use crate::receipts::*;
+pub use crate::stats::*;
+pub use crate::templates::*;
+pub use crate::time::*;
 
 mod internal;
 mod approval; 
@@ -25,11 +41,33 @@ mod nft_core;
 mod royalty; 
 mod events;
 mod migrate;
+mod storage;
+mod sponsorship;
+mod roles;
+mod ownership;
+mod pause;
+mod governance;
+mod audit;
+mod burn;
+mod sweep;
+mod expiration;
+mod locks;
+mod account;
+mod receipts;
+mod stats;
+mod templates;
+mod time;
 
 /// This spec can be treated like a version of the standard.
 pub const NFT_METADATA_SPEC: &str = "1.0.0";
 /// This is the name of the NFT standard we're using
 pub const NFT_STANDARD_NAME: &str = "nep171";
+/// This is the name of the standard used for the events specific to this contract
+pub const OWA_STANDARD_NAME: &str = "owa_subscriptions";
+/// Version of the owa_subscriptions events
+pub const OWA_EVENT_VERSION: &str = "1.0.0";
+/// Gateway set as base_uri so wallets can resolve the IPFS CIDs of the media and the reference of the tokens
+pub const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs";
 pub const ICON: &str = "data:image/jpeg;base64,/9j/4AAQSkZJRgABAQAAAQABAAD/4gHYSUNDX1BST0ZJTEUAAQEAAAHIAAAAAAQwAABtbnRyUkdCIFhZWiAAAAAAAAAAAAAAAABhY3NwAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAA9tYAAQAAAADTLQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAlkZXNjAAAA8AAAACRyWFlaAAABFAAAABRnWFlaAAABKAAAABRiWFlaAAABPAAAABR3dHB0AAABUAAAABRyVFJDAAABZAAAAChnVFJDAAABZAAAAChiVFJDAAABZAAAAChjcHJ0AAABjAAAADxtbHVjAAAAAAAAAAEAAAAMZW5VUwAAAAgAAAAcAHMAUgBHAEJYWVogAAAAAAAAb6IAADj1AAADkFhZWiAAAAAAAABimQAAt4UAABjaWFlaIAAAAAAAACSgAAAPhAAAts9YWVogAAAAAAAA9tYAAQAAAADTLXBhcmEAAAAAAAQAAAACZmYAAPKnAAANWQAAE9AAAApbAAAAAAAAAABtbHVjAAAAAAAAAAEAAAAMZW5VUwAAACAAAAAcAEcAbwBvAGcAbABlACAASQBuAGMALgAgADIAMAAxADb/2wBDAAMCAgICAgMCAgIDAwMDBAYEBAQEBAgGBgUGCQgKCgkICQkKDA8MCgsOCwkJDRENDg8QEBEQCgwSExIQEw8QEBD/2wBDAQMDAwQDBAgEBAgQCwkLEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBD/wAARCABgAGADASIAAhEBAxEB/8QAHgAAAQUBAQEBAQAAAAAAAAAACAAFBgcJBAIDAQr/xAA+EAABAwMCAwUDCgQGAwAAAAABAgMEAAUGBxESITEIEyJBUWGRoQkUFRYjMkJxscFSU2OBMzRDcpLwYqLx/8QAGwEAAgMBAQEAAAAAAAAAAAAABgcEBQgCAAP/xAA0EQABAgQDBAgFBQEAAAAAAAABAgMABAUGETFREiFBgQcTIiNhcZGxMjNS0eEUFRahwUL/2gAMAwEAAhEDEQA/ANU6VKlXo9CrytaG0lS1hIHmTtUK1K1axbTO2qlXiWhUhQPdR0nxrNCJqB2ls1zV9xiBLVbYCiQGmjzI9vWiih2lUK53jY2W/qOXLWAm6L8pdsDq3Ttu/QnPmchBkX/U/BsbChdchitLT+DiJPwqEy+07gTTvcwUSZqt9h3Wx3oVtO9O8u1Suoahh4scX20lzcpSKLzTzQbD8HjtuuxETpwAKnnRvsfZVzVKHQ7eHVzTinXfpGA9dIEqNc93XevrKeyiXY+tQKvTX0wjuxrU64ZPwuQ8MuaGVdHVhAG3/Kp42pS20qWgpJHMHypIbZYRwtoShI9BsBUVy3VbAcJbUvIskhxVJ/ApfP4UGOATrmzKM4eAxUYZkqHKYxtVGZ2jqQlI5YfcxLaVUMrtq6DNTvmUnK2WfFw94onh/SrdxXNcWza3N3bFr3GuMVwbpcZVuK5mKfNyg2n21JHiDEuXqEpN/IcSryIh8pUqVQ4mQqg+ruptr0vxORfJq0l8pKYzRPNa/T9amrriWWlurOyUJKifYKzm7UGsbuoudSLXBfJtdocLLIB5LP8AF8SKK7Ot83BUUsr+Wnery05wKXlXjQKYp5v5iuynzPHlEOzDPL3nV8kXu9THHnHlkpSTyQPQVKtGdM7pqhkrNtjIUiG0oKkvbckp/wC7VVdvS9NlNRGElbjywhIHmTWkOg+nlu0t09YdloQ3LkNB+Y4rkQfT3AU5LvrLdsU4NywAWrsoGnjy94Q1oWyu7KqXJwktp7Szr4Y+PtE5w/D7LhNlYs1mioZaZSApQHNR9TXfMvVqt7a3Zc9ltKBurdXSqmyXM8my2O5IsU36KtCVcDb/APqSfanqNvz26U42TTqwJSj6RYcmPLTu46+rms/2O1Z0cdW+4XHTiScSY08yw3LNBlgBKQMABkIqHXjtL3YokWHBlLjNDdC5g6q/2/8Ayghzi73u7SHZdxuT77qySVLVvua00yTRrB7zEUyuytt+HkUDr76pHKuwtb79IfVZsjVFLm5aQ4PCPZyTTOt25aLJM9T1fVHXPHnnCbuGzrkm5z9U48H047gOzsjQJJw/vGM3L9Mf3U0+oqB6E+VXl2N9ScxwaXOkQbk+YjT4+yUrdChsOVdWuXYw1a0+YeuTVrN2gN7lT8VJISPU77V+6U44cbxtll5vgkP+N0HqFdP2o6lnZSrtnYIWg58YEromZm3qeE4Ft4kYcCCN+IjUbTjP7ZqDjzN3grAd4QHmt+aFelSygl7OmfPYpl7FveeIh3FQaWknkk9d/hRspUFpCx0I3FJe6KJ+yTxbR8Ct6fLTlDd6P7r/AJXSQ8781HZX58DzG+K17RudDTvRzJcmS5wPR4au6O/4iQP3rKSxXVy4w0zH1lTjxK1KJ5kkmjx+Upvi7VoIqIhwp+fySyQPMcJO3wrOXArql60soKubY4FU0uiZhCJd1w/Es+0DnSalT4bSMk/7BOdlzFE5jqva2Xm+NiEsPugjltzH6mjj1pu8hqHacNt4Jdvb4jKSjrwcJP6poX+wFEafzK8zFEFTcMBP/NNE7qNPj2fUPGbnOhqeYB4EqA/w1+I8XuoR6UJpT1a6g5ISAOe8xddGskiWpBeGa1EnluEe7Lb7fZrRHxdTKXLs6nuCF/gT13Hs/enxu0XG0S4Vsizg9urvF991I2I8q4LPCt+a3u5X1EvhMZz5vFebPiSnYK3G/tJr6x4GRJy8sNXZMgMRfvP9fvewUt4YcPsWC+9Kdhz3x4PG2E9NqdG0BZDTgHG2eSh5impyJcWrhGemSxu4e7V3fTbmfOo/qxqtjelOPuXGfJQqYpJEdgK8Tiq+0vLuzboZZGKjkIjTc2zIsqmJhQSlIxJMRXtL6rwcJxVyxRXG3LlckFtKeRKE/wAW39qCNh0uKKz1UdzSzLO7xn2RScgvMhS1vLJQknkhPoKiN3zmLZ1BiIUvSB1HkPzrQFr0H9olQynetW9R8fxGUbwq81etVxYSdhO5I0Gp884IfR3Ti6ZXdWrk6+IFvirDi5Lh4Ry8hRy2x2O9AYXFkJfa4AEuJO4Vty3rJ2BneomdvM2k3uQxbWtt2WjshKfdvWkvZ9KxpXZGVrKu6YCQSdyeZoP6RKbMtttzUwsZ4BIGQPEniThDS6MkU2ivrpEvip5SdtauG44AYcM/OKO+Uvsy7loEZraFK+j5ReVt5DhI3PvrK3C759Gyu5dV9k7yPsPrW3vaRwAalaNZNiqGwp+TCV3PLooEH9BWFcyDJs90k2yW0pt6K6ptSSNiCDUno8qBalilB7SFY8jBVdsmHldsblD2g++whnEey6om1SX0hu7MBhsk/i4gf2o9dS8elXm0NXK1pCp9qX85jpI++diNvcTWJGnuol6wm9Qb1b3ld7BdDjZB5jatGMR+Ul06fscVGRWG4InJbCXuAo4Sr1G5rnpBpDtWnEVCRQVFQwUOII4+REQ7QqLNLllSU2oJAOIOoMXVpezbL7GnymJb9on/ADnZ1DewPFwjyO9SC1xLvHy2cF3wrDTPNx0gbp3HoKE7MO3Tgbc1VzwTGprct1XE6lwoDTv+7ZW/p0qmc47VOoubzHnYUz6Gjvo7tbUVR2Unffnxb0HyNmVObUOsTsDU/aLmo3pTJFBKFbatB94M/WztL43p6wbfb7u3dby2TwtMq3S2rbqrpQSZvqTfc4ur1/yi5rcKiVALV4UD0FVPfc6g2vjkT5hkSVc+Eq3UTVfXjN7nf3eAuFpjfk2k+VNW37YlaQnFsYrOajny0hWVecq13r73u2BkOH5P9RZN+1H70qg2dRCeinQev5V8MUst0ye4JajIWvc7uLPQCmnTvTq+ZfJS4GVswwRxvKHL+1ExjOLWzF4CIcBkAgeNe3NR9TRkHUS6cEZwI1ityNssGUkgFPHPw8Sf8j3jmPRLBBbiR0Dj2HGrzUaPrQiM7G0ztCXRsVM7j3mgns8B66XSLAYQVOPupSAPOtA8UtSbJjsC1oGwjshP7/vSp6SJsfpmmCe0pRPID8xddCMu/OVSaqTpJwSBjqVHH2EOi0JcQptY3SoEEeorLHt89k+7YdlknVLDbY5Is90WXZiGUf5dfr+WwHvrVCuW52u3XmG7b7pDalRnk8K23E7pUKXFGq7tGmQ83vGRGojQ8/Ion2i2rceBj+fOMFoPCtJSR5EbU7xXUpAKlAfmaLv5RiNoppChrGMD05h/Wm8ILqn2kq2jp3I4h4uu4+NZ7NWjUm9+BuNLCFfltTrpdRFTlxMpQUg5YwvJ6g9Ssh55KR4mLQm5lYrK2TKmoKx0QDzNRO7aszp+8e0IMdvpx/iNc1o0LyS4rDt1ntRknmQoniqzcX0bw6yKS9cXFT3E89nNuHf+21WzeJzEUb71ApY2lKLy9AMR9v7itcdsWTZbMSiDDfkrcPNZHIUQuneg9vtxbuGWSEvuDZQjp+6Pzp0ttxtVnZTHtsZqO2kbAIFdv1qH8741LDmAwgFrtx1KpgsyieqR4fF68OUWdCftdujoiwmUNNoGwSkbCuj6VjfxCqp+tQ/nfGrD0awTJtXckZtdoYc+aIUDJkEeFtP/AHavhMTbUq2XnlYJGZgDl7Wmp58NNAqWowRPZhwY5JfzlEtg/NLed2yRyUvl+xovAABsOgpgwbDbXguORMftbSUojoCVKA5rV6mpBSAuKsqrc6p//kbkjw/Ma0sm127TpSZMb1ntLOqj9soVKlSqigvimO0B2ZMO1ygd/LaREvTCClialO6vPkd9+XP0rN/WHs66saPTnhcLA/MtqCeCbHRu2pPr6/Cth655kCFcWVR50Vp9pY2UlxIIIono11TlIAa+JvQ8PIwOVe2JKrK61Y2V6jj5xg27kjzCih4LbUORCgQa8fWv+p8a2Bz3sc6C6gLcfuWEw48lwkqfYSQrf15naqZvPyXOkM58u2/JLxESTvwJDew/9aOZe/JBwd6FJPlj7QKOWIpJ7BBjOT61/wBT410wr1PuLyY8Fh59xZ2SltJJJrR3Hvkw9F7W6l253i6XAA78DoRsfcBV7afdmbRjTUIXjWEwGpCNvt1IJV8TtXEzfsi2nuElR9BHTVhlR7wgD1gCdBOxzqXqbJj3bKYr1jspIWVPDZbqf/Hr8a0a010uxTS2wNWLGbe2ylCR3ju3icV6mpY202ygNtNpQkcgEjYCvdAFYuKcrKsHTgjgkZc9YL6Rb0lR+0ynFf1HP8QqVKlVDF7H/9k=";
 
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, Clone)]
@@ -53,7 +91,7 @@ pub struct TokensCounter {
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct OldContract {
+pub struct Contract {
     //contract owner
     pub owner_id: AccountId,
 
@@ -63,7 +101,7 @@ pub struct OldContract {
     //keeps track of the token struct for a given token ID
     pub tokens_by_id: LookupMap<TokenId, Token>,
 
-    //keeps track of the token metadata for a given token ID
+    //keeps track of the full token metadata of the tokens from before the plan templates, emptied by the token migration
     pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
 
     //keeps track of the metadata for the contract
@@ -75,33 +113,100 @@ pub struct OldContract {
     pub six_months_cost: U128,
     pub one_month_cost: U128,
     pub usdt_contract: String,
-}
 
+    //keeps track of the NEP-145 storage balance registered by each account
+    pub storage_balances: LookupMap<AccountId, StorageBalance>,
 
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct Contract {
-    //contract owner
-    pub owner_id: AccountId,
+    //NEAR funded by the owner to pay for the storage of sponsored plans and accounts
+    pub storage_pool: Balance,
+    pub sponsored_plans: UnorderedSet<String>,
+    pub sponsored_accounts: LookupSet<AccountId>,
+    //keeps track of how much storage the pool has paid, per account and in total
+    pub sponsored_storage_per_account: LookupMap<AccountId, Balance>,
+    pub sponsored_storage_total: Balance,
+    pub sponsored_storage_account_cap: Balance,
+    pub sponsored_storage_global_cap: Balance,
 
-    //keeps track of all the token IDs for a given account
-    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    //keeps track of the accounts that have been granted each role
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
 
-    //keeps track of the token struct for a given token ID
-    pub tokens_by_id: LookupMap<TokenId, Token>,
+    //ownership transfer waiting to be accepted and how long the new owner has to wait to accept it
+    pub pending_owner: Option<PendingOwner>,
+    pub ownership_transfer_delay: u64,
 
-    //keeps track of the token metadata for a given token ID
-    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    //upgrade waiting to be deployed and how long it has to wait after being staged
+    pub staged_upgrade: Option<StagedUpgrade>,
+    pub upgrade_timelock: u64,
+    //change of the timelock that applies once the current timelock has passed
+    pub pending_upgrade_timelock: Option<PendingUpgradeTimelock>,
 
-    //keeps track of the metadata for the contract
-    pub metadata: LazyOption<NFTContractMetadata>,
+    //tokens still being rewritten after migrating the state from an older version
+    pub token_migration: Option<TokenMigration>,
 
-    pub tokens_to_mint_counter: LookupMap<AccountId, TokensCounter>,
-    pub permanent_cost: U128,
-    pub one_year_cost: U128,
-    pub six_months_cost: U128,
-    pub one_month_cost: U128,
-    pub usdt_contract: String,
+    //parts of the contract that are currently paused
+    pub pause_status: PauseStatus,
+
+    //how configuration changes are approved and the proposals made to the council
+    pub governance: GovernanceMode,
+    pub config_proposals: UnorderedMap<u64, ConfigProposal>,
+    pub next_config_proposal_id: u64,
+
+    //last privileged actions, kept as a ring buffer of MAX_AUDIT_LOG_ENTRIES entries
+    pub audit_log: LookupMap<u64, AuditEntry>,
+    pub next_audit_entry_id: u64,
+
+    //ID of the next token to mint, so IDs are never reused after a burn
+    pub next_token_id: u64,
+    //whether the token IDs are prefixed with the subscription type, ex. year-000123
+    pub plan_prefixed_token_ids: bool,
+
+    //who paid for the storage of each token
+    pub token_storage_payers: LookupMap<TokenId, StoragePayer>,
+    //how long expired tokens are kept and the bounty for sweeping each one
+    pub sweep_retention_period: u64,
+    pub sweep_bounty: Balance,
+
+    //tokens that expire ordered by their expiration
+    pub expiry_index: TreeMap<(u64, TokenId), ()>,
+    //last tokens of the index marked as expired and notified as expiring soon by process_expirations
+    pub expiration_cursor: Option<(u64, TokenId)>,
+    pub expiring_soon_cursor: Option<(u64, TokenId)>,
+    //how long (in milliseconds) before the expiration the expiring soon notice is logged, 0 if disabled
+    pub expiring_soon_notice: u64,
+
+    //keeps track of all the token IDs for a given subscription type
+    pub tokens_per_type: LookupMap<String, UnorderedSet<TokenId>>,
+    //subscription types that can't be minted or transferred
+    pub token_types_locked: UnorderedMap<String, TypeLock>,
+
+    //receipts of the payments by ID and the IDs of the receipts of each account
+    pub receipts: LookupMap<u64, Receipt>,
+    pub receipts_per_account: LookupMap<AccountId, Vector<u64>>,
+    pub next_receipt_id: u64,
+    //IDs of the receipts of each beneficiary and subscription type that have no token yet
+    pub unlinked_receipts: LookupMap<(AccountId, String), Vector<u64>>,
+
+    //counters for the statistics of the contract
+    pub revenue_per_plan: UnorderedMap<String, Balance>,
+    pub revenue_per_token: UnorderedMap<String, Balance>,
+    pub subscribers: LookupSet<AccountId>,
+    pub unique_subscribers: u64,
+    pub payments_count: u64,
+    pub renewals_count: u64,
+    pub lifetime_subscriptions: u64,
+    pub expired_subscriptions: u64,
+    pub pending_credits: u64,
+
+    //fixed expiration (in milliseconds) of the subscription types that end on a date instead of lasting some months
+    pub plan_end_dates: UnorderedMap<String, u64>,
+
+    //keeps track of the subscription type and the dates of a given token ID, the rest of its metadata is the template of its type
+    pub token_data_by_id: UnorderedMap<TokenId, TokenData>,
+    pub plan_templates: UnorderedMap<String, PlanTemplate>,
+
+    //token IDs ordered by when they were minted, for all the tokens and per owner, so the cursors of the pages don't move on burns or transfers
+    pub tokens_in_order: TreeMap<u64, TokenId>,
+    pub tokens_per_owner_in_order: TreeMap<(AccountId, u64), TokenId>,
 }
 
 /// Helper structure for keys of the persistent collections.
@@ -116,6 +221,29 @@ pub enum StorageKey {
     TokensPerTypeInner { token_type_hash: CryptoHash },
     TokenTypesLocked,
     TokensToMintCounter,
+    StorageBalances,
+    SponsoredPlans,
+    SponsoredAccounts,
+    SponsoredStoragePerAccount,
+    RoleMembers,
+    RoleMembersInner { role: Role },
+    ConfigProposals,
+    AuditLog,
+    TokenStoragePayers,
+    ExpiryIndex,
+    Receipts,
+    ReceiptsPerAccount,
+    ReceiptsPerAccountInner { account_id_hash: CryptoHash },
+    RevenuePerPlan,
+    RevenuePerToken,
+    Subscribers,
+    PlanEndDates,
+    TokenDataById,
+    PlanTemplates,
+    UnlinkedReceipts,
+    UnlinkedReceiptsInner { account_id_hash: CryptoHash, token_type_hash: CryptoHash },
+    TokensInOrder,
+    TokensPerOwnerInOrder,
 }
 
 #[near_bindgen]
@@ -135,7 +263,7 @@ impl Contract {
                 name: "OWA".to_string(),
                 symbol: "OWA".to_string(),
                 icon: Some(ICON.to_string()),
-                base_uri: None,
+                base_uri: Some(IPFS_GATEWAY.to_string()),
                 reference: None,
                 reference_hash: None,
             },
@@ -150,7 +278,7 @@ impl Contract {
     #[init]
     pub fn new(owner_id: AccountId, metadata: NFTContractMetadata) -> Self {
         //create a variable of type Self with all the fields initialized. 
-        let this = Self {
+        let mut this = Self {
             //Storage keys are simply the prefixes used for the collections. This helps avoid data collision
             tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner.try_to_vec().unwrap()),
             tokens_by_id: LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()),
@@ -168,9 +296,68 @@ impl Contract {
             one_year_cost: near_sdk::json_types::U128(100000000), //100 USDT.e
             six_months_cost: near_sdk::json_types::U128(50000000), //50 USDT.e
             one_month_cost: near_sdk::json_types::U128(10000000), //10 USDT.e
-            usdt_contract: "usdt.fakes.testnet".to_string()
+            usdt_contract: "usdt.fakes.testnet".to_string(),
+            storage_balances: LookupMap::new(StorageKey::StorageBalances.try_to_vec().unwrap()),
+            storage_pool: 0,
+            sponsored_plans: UnorderedSet::new(StorageKey::SponsoredPlans.try_to_vec().unwrap()),
+            sponsored_accounts: LookupSet::new(StorageKey::SponsoredAccounts.try_to_vec().unwrap()),
+            sponsored_storage_per_account: LookupMap::new(StorageKey::SponsoredStoragePerAccount.try_to_vec().unwrap()),
+            sponsored_storage_total: 0,
+            sponsored_storage_account_cap: DEFAULT_SPONSORED_STORAGE_PER_ACCOUNT,
+            sponsored_storage_global_cap: DEFAULT_SPONSORED_STORAGE_TOTAL,
+            role_members: LookupMap::new(StorageKey::RoleMembers.try_to_vec().unwrap()),
+            pending_owner: None,
+            ownership_transfer_delay: 0,
+            staged_upgrade: None,
+            upgrade_timelock: DEFAULT_UPGRADE_TIMELOCK,
+            pending_upgrade_timelock: None,
+            token_migration: None,
+            pause_status: PauseStatus::default(),
+            governance: GovernanceMode::Direct,
+            config_proposals: UnorderedMap::new(StorageKey::ConfigProposals.try_to_vec().unwrap()),
+            next_config_proposal_id: 0,
+            audit_log: LookupMap::new(StorageKey::AuditLog.try_to_vec().unwrap()),
+            next_audit_entry_id: 0,
+            next_token_id: 0,
+            plan_prefixed_token_ids: false,
+            token_storage_payers: LookupMap::new(StorageKey::TokenStoragePayers.try_to_vec().unwrap()),
+            sweep_retention_period: DEFAULT_SWEEP_RETENTION_PERIOD,
+            sweep_bounty: DEFAULT_SWEEP_BOUNTY,
+            expiry_index: TreeMap::new(StorageKey::ExpiryIndex.try_to_vec().unwrap()),
+            expiration_cursor: None,
+            expiring_soon_cursor: None,
+            expiring_soon_notice: 0,
+            tokens_per_type: LookupMap::new(StorageKey::TokensPerType.try_to_vec().unwrap()),
+            token_types_locked: UnorderedMap::new(StorageKey::TokenTypesLocked.try_to_vec().unwrap()),
+            receipts: LookupMap::new(StorageKey::Receipts.try_to_vec().unwrap()),
+            receipts_per_account: LookupMap::new(StorageKey::ReceiptsPerAccount.try_to_vec().unwrap()),
+            next_receipt_id: 0,
+            unlinked_receipts: LookupMap::new(StorageKey::UnlinkedReceipts.try_to_vec().unwrap()),
+            revenue_per_plan: UnorderedMap::new(StorageKey::RevenuePerPlan.try_to_vec().unwrap()),
+            revenue_per_token: UnorderedMap::new(StorageKey::RevenuePerToken.try_to_vec().unwrap()),
+            subscribers: LookupSet::new(StorageKey::Subscribers.try_to_vec().unwrap()),
+            unique_subscribers: 0,
+            payments_count: 0,
+            renewals_count: 0,
+            lifetime_subscriptions: 0,
+            expired_subscriptions: 0,
+            pending_credits: 0,
+            plan_end_dates: UnorderedMap::new(StorageKey::PlanEndDates.try_to_vec().unwrap()),
+            token_data_by_id: UnorderedMap::new(StorageKey::TokenDataById.try_to_vec().unwrap()),
+            plan_templates: default_plan_templates(),
+            tokens_in_order: TreeMap::new(StorageKey::TokensInOrder.try_to_vec().unwrap()),
+            tokens_per_owner_in_order: TreeMap::new(StorageKey::TokensPerOwnerInOrder.try_to_vec().unwrap()),
         };
 
+        //the owner starts with every role
+        let owner_id = this.owner_id.clone();
+        for role in ROLES {
+            this.internal_grant_role(role, &owner_id);
+        }
+
+        //keep track of the layout the state is stored in for future migrations
+        write_state_version();
+
         //return the Contract object
         this
     }
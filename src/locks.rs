@@ -0,0 +1,75 @@
+use crate::*;
+
+//what a lock stops for the subscriptions of its type
+const LOCKED_SCOPES: [PauseScope; 3] = [PauseScope::Payments, PauseScope::Minting, PauseScope::Transfers];
+
+//a lock on a subscription type. Tokens of a locked type can't be minted or transferred
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TypeLock {
+    pub type_suscription: String,
+    //timestamp (in milliseconds, like the dates of the tokens) at which the lock ends, ex. at the end of a migration window. None if it lasts until it's removed
+    pub locked_until: Option<U64>,
+}
+
+#[near_bindgen]
+impl Contract {
+    //lock a subscription type, until the passed in timestamp if there is one
+    pub fn lock_type(&mut self, type_suscription: String, locked_until: Option<U64>) -> TypeLock {
+        self.assert_role(Role::Admin);
+        require!(is_valid_plan(&type_suscription), "The subscription type does not exist");
+
+        let type_lock = TypeLock {
+            type_suscription: type_suscription.clone(),
+            locked_until,
+        };
+        let old_lock = self.token_types_locked.insert(&type_suscription, &type_lock);
+        self.internal_log_admin_action(
+            "lock_type",
+            old_lock.map(|lock| json!(lock).to_string()),
+            Some(json!(type_lock).to_string()),
+        );
+        //a locked type can't be paid, minted nor transferred
+        self.internal_log_event(EventLogVariant::SubscriptionPaused(vec![SubscriptionPauseLog {
+            type_suscriptions: vec![type_suscription],
+            scopes: LOCKED_SCOPES.iter().map(|scope| scope.as_str().to_string()).collect(),
+            until: locked_until,
+        }]));
+        type_lock
+    }
+
+    //remove the lock of a subscription type
+    pub fn unlock_type(&mut self, type_suscription: String) {
+        self.assert_role(Role::Admin);
+        let old_lock = self.token_types_locked.remove(&type_suscription).expect("The subscription type is not locked");
+        self.internal_log_admin_action("unlock_type", Some(json!(old_lock).to_string()), None);
+        self.internal_log_event(EventLogVariant::SubscriptionResumed(vec![SubscriptionPauseLog {
+            type_suscriptions: vec![type_suscription],
+            scopes: LOCKED_SCOPES.iter().map(|scope| scope.as_str().to_string()).collect(),
+            until: None,
+        }]));
+    }
+
+    //check if a subscription type is locked right now
+    pub fn is_type_locked(&self, type_suscription: String) -> bool {
+        self.token_types_locked
+            .get(&type_suscription)
+            .map(|type_lock| type_lock.locked_until.map(|until| now_ms() < until.0).unwrap_or(true))
+            .unwrap_or(false)
+    }
+
+    //get the locks of every subscription type, including the ones whose window already ended
+    pub fn get_type_locks(&self) -> Vec<TypeLock> {
+        self.token_types_locked.values().collect()
+    }
+}
+
+impl Contract {
+    //make sure the passed in subscription type is not locked
+    pub(crate) fn assert_type_not_locked(&self, type_suscription: &str) {
+        require!(
+            !self.is_type_locked(type_suscription.to_string()),
+            format!("The subscription type {} is locked", type_suscription)
+        );
+    }
+}
@@ -36,6 +36,17 @@ pub struct TokenMetadata {
     pub reference_hash: Option<Base64VecU8>, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
 }
 
+//what is stored for each token besides the Token struct. The rest of its metadata comes from the template of its subscription type
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenData {
+    pub type_suscription: String,
+    //dates of the token, Unix epoch in milliseconds
+    pub issued_at: Option<u64>,
+    pub starts_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub updated_at: Option<u64>,
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Token {
     //owner of the token
@@ -64,6 +75,15 @@ pub struct JsonToken {
     pub royalty: HashMap<AccountId, u32>,
 }
 
+//A page of Json tokens returned by the cursor based views
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenPage {
+    pub tokens: Vec<JsonToken>,
+    //cursor to pass to get the next page, none if this is the last one
+    pub next_cursor: Option<String>,
+}
+
 pub trait NonFungibleTokenMetadata {
     //view call for returning the contract metadata
     fn nft_metadata(&self) -> NFTContractMetadata;
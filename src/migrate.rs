@@ -1,20 +1,158 @@
 use crate::*;
+use near_sdk::json_types::Base58CryptoHash;
 use near_sdk::{Gas};
 
 /// Gas for upgrading this contract on promise creation + deploying new contract.
 pub const TGAS: u64 = 10_000_000_000_000;
 pub const GAS_FOR_UPGRADE_SELF_DEPLOY: Gas = Gas(300_000_000_000_000);
 pub const GAS_FOR_UPGRADE_REMOTE_DEPLOY: Gas = Gas(300_000_000_000_000);
+/// Default time (in milliseconds) between staging an upgrade and being able to deploy it: 1 day.
+pub const DEFAULT_UPGRADE_TIMELOCK: u64 = MILLISECONDS_PER_DAY;
+/// Shortest time (in milliseconds) the upgrade timelock can be set to: 1 hour.
+pub const MIN_UPGRADE_TIMELOCK: u64 = 3_600_000;
+/// Version of the layout of the state. Bump it when a new layout is deployed.
+pub const STATE_VERSION: u16 = 3;
+/// Storage key where the version of the layout of the state is kept.
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+/// Default amount of tokens rewritten by each call to migrate_tokens.
+pub const DEFAULT_TOKEN_MIGRATION_BATCH: u64 = 50;
 
+/// Upgrade waiting for its timelock to pass before it can be deployed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedUpgrade {
+    //sha256 of the wasm that will be deployed
+    pub code_hash: Base58CryptoHash,
+    //account that staged the upgrade
+    pub staged_by: AccountId,
+    //block timestamp (in milliseconds) from which the upgrade can be deployed
+    pub deploy_after: U64,
+}
+
+/// Change of the upgrade timelock waiting for the current timelock to pass before it applies.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingUpgradeTimelock {
+    //time (in milliseconds) that will have to pass between staging an upgrade and deploying it
+    pub timelock: U64,
+    //block timestamp (in milliseconds) from which the new timelock applies
+    pub effective_after: U64,
+}
+
+/// Token migration in progress after the state was migrated from an older version.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMigration {
+    //version the tokens are being migrated from
+    pub from_version: u16,
+    //amount of tokens rewritten so far
+    pub next_index: u64,
+}
 
 #[near_bindgen]
 impl Contract {
+    /*
+        first step of the upgrade: the new wasm is passed as the raw input of the call
+        and only its sha256 is stored, together with the time from which it can be deployed
+    */
+    pub fn stage_upgrade(&mut self) -> StagedUpgrade {
+        self.assert_role(Role::Upgrader);
+        let code = env::input().expect("The new contract code must be passed as input");
+        let code_hash: CryptoHash = env::sha256(&code).try_into().unwrap();
+
+        let staged_upgrade = StagedUpgrade {
+            code_hash: code_hash.into(),
+            staged_by: env::predecessor_account_id(),
+            deploy_after: U64(now_ms() + self.internal_upgrade_timelock()),
+        };
+        self.staged_upgrade = Some(staged_upgrade.clone());
+        self.internal_audit("stage_upgrade", None, Some(json!(staged_upgrade).to_string()));
+
+        self.internal_log_event(EventLogVariant::UpgradeStaged(vec![UpgradeLog {
+            code_hash: String::from(&staged_upgrade.code_hash),
+            deploy_after: Some(staged_upgrade.deploy_after),
+        }]));
+
+        staged_upgrade
+    }
+
+    //discard the staged upgrade
+    pub fn cancel_upgrade(&mut self) {
+        self.assert_role(Role::Upgrader);
+        let staged_upgrade = self.staged_upgrade.take().expect("There is no staged upgrade");
+        self.internal_audit("cancel_upgrade", Some(json!(staged_upgrade).to_string()), None);
+
+        self.internal_log_event(EventLogVariant::UpgradeCancelled(vec![UpgradeLog {
+            code_hash: String::from(&staged_upgrade.code_hash),
+            deploy_after: None,
+        }]));
+    }
+
+    pub fn get_staged_upgrade(&self) -> Option<StagedUpgrade> {
+        self.staged_upgrade.clone()
+    }
+
+    /*
+        set the time (in milliseconds) that has to pass between staging an upgrade and deploying it.
+        the new timelock only applies once the current one has passed, so it can't be lowered to deploy an upgrade right away
+    */
+    pub fn set_upgrade_timelock(&mut self, timelock: U64) -> PendingUpgradeTimelock {
+        self.assert_role(Role::Admin);
+        require!(
+            timelock.0 >= MIN_UPGRADE_TIMELOCK,
+            format!("The upgrade timelock must be at least {} milliseconds", MIN_UPGRADE_TIMELOCK)
+        );
+
+        //a previous change whose timelock already passed is the one in force now
+        self.upgrade_timelock = self.internal_upgrade_timelock();
+        let pending_timelock = PendingUpgradeTimelock {
+            timelock,
+            effective_after: U64(now_ms() + self.upgrade_timelock),
+        };
+        self.pending_upgrade_timelock = Some(pending_timelock.clone());
+        self.internal_log_admin_action(
+            "set_upgrade_timelock",
+            Some(json!(U64(self.upgrade_timelock)).to_string()),
+            Some(json!(pending_timelock).to_string()),
+        );
+        pending_timelock
+    }
+
+    //get the timelock in force right now
+    pub fn get_upgrade_timelock(&self) -> U64 {
+        U64(self.internal_upgrade_timelock())
+    }
+
+    pub fn get_pending_upgrade_timelock(&self) -> Option<PendingUpgradeTimelock> {
+        self.pending_upgrade_timelock
+            .clone()
+            .filter(|pending_timelock| now_ms() < pending_timelock.effective_after.0)
+    }
+
+    /*
+        second step of the upgrade: the same wasm that was staged is passed as the raw input of the call.
+        it is only deployed if its sha256 matches the staged one and the timelock has passed
+    */
     #[cfg(target_arch = "wasm32")]
     pub fn upgrade(self) {
         use near_sys as sys;
         log!("predecessor_account_id: {}",env::predecessor_account_id());
         log!("owner_id: {}",self.owner_id);
-        //assert!(env::predecessor_account_id() == self.owner_id);
+        self.assert_role(Role::Upgrader);
+        let staged_upgrade = self.staged_upgrade.clone().expect("There is no staged upgrade");
+        require!(
+            now_ms() >= staged_upgrade.deploy_after.0,
+            "The staged upgrade can't be deployed yet"
+        );
+        let code_hash: CryptoHash = staged_upgrade.code_hash.into();
+        require!(
+            env::sha256(&env::input().expect("The new contract code must be passed as input")) == code_hash,
+            "The code doesn't match the staged upgrade"
+        );
+        self.internal_log_event(EventLogVariant::UpgradeDeployed(vec![UpgradeLog {
+            code_hash: String::from(&staged_upgrade.code_hash),
+            deploy_after: None,
+        }]));
         //input is code:<Vec<u8> on REGISTER 0
         //log!("bytes.length {}", code.unwrap().len());
         const GAS_FOR_UPGRADE: u64 = 20 * TGAS; //gas occupied by this fn
@@ -50,25 +188,338 @@ impl Contract {
 
 /////////////////////METODO DE MIGRACIÖN
  
+    /*
+        called on the new code right after it is deployed.
+        reads the state in whatever layout it was stored, upgrades it one version at a time until it reaches
+        the current one and, if the tokens need to be rewritten too, starts a token migration that is
+        completed in batches through migrate_tokens
+    */
     #[private]
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let old_state: OldContract = env::state_read().expect("failed");
-        env::log_str("old state readed");
+        let from_version = stored_state_version();
+        log!("migrating state from version {} to {}", from_version, STATE_VERSION);
+
+        let mut this: Contract = match from_version {
+            1 => {
+                let state: ContractV1 = env::state_read().expect("failed");
+                ContractV2::from(state).into()
+            }
+            2 => {
+                let state: ContractV2 = env::state_read().expect("failed");
+                state.into()
+            }
+            STATE_VERSION => env::state_read().expect("failed"),
+            _ => env::panic_str("Unknown state version"),
+        };
+
+        //the upgrade that has just been deployed is no longer staged
+        this.staged_upgrade = None;
+
+        if from_version < STATE_VERSION && !this.token_metadata_by_id.is_empty() {
+            this.token_migration = Some(TokenMigration {
+                from_version,
+                next_index: 0,
+            });
+        }
+
+        write_state_version();
+        this
+    }
+
+    //rewrite the next batch of tokens of the migration in progress. Returns the migration if there are tokens left
+    pub fn migrate_tokens(&mut self, limit: Option<u64>) -> Option<TokenMigration> {
+        self.assert_role(Role::Upgrader);
+        let mut token_migration = self.token_migration.clone().expect("There is no token migration in progress");
+
+        /*
+            the tokens are moved out of the full metadata they were stored with, so take them from the end of it
+            where removing them doesn't move the ones left. Every batch costs the same
+        */
+        let mut rewritten = 0;
+        while rewritten < limit.unwrap_or(DEFAULT_TOKEN_MIGRATION_BATCH) && !self.token_metadata_by_id.is_empty() {
+            let last_index = self.token_metadata_by_id.len() - 1;
+            let token_id = self.token_metadata_by_id.keys_as_vector().get(last_index).unwrap();
+            self.internal_migrate_token(&token_id, token_migration.from_version);
+            rewritten += 1;
+        }
+        token_migration.next_index += rewritten;
+
+        if self.token_metadata_by_id.is_empty() {
+            log!("token migration from version {} completed", token_migration.from_version);
+            self.token_migration = None;
+        } else {
+            self.token_migration = Some(token_migration);
+        }
+
+        self.token_migration.clone()
+    }
+
+    pub fn get_token_migration(&self) -> Option<TokenMigration> {
+        self.token_migration.clone()
+    }
+
+    pub fn get_state_version(&self) -> u16 {
+        stored_state_version()
+    }
+}
+
+impl Contract {
+    //the upgrade timelock in force, including a change whose own timelock already passed
+    pub(crate) fn internal_upgrade_timelock(&self) -> u64 {
+        match &self.pending_upgrade_timelock {
+            Some(pending_timelock) if now_ms() >= pending_timelock.effective_after.0 => pending_timelock.timelock.0,
+            _ => self.upgrade_timelock,
+        }
+    }
+}
+
+impl Contract {
+    /*
+        rewrite a single token stored by the passed in state version in the current layout.
+        steps for new versions go here in order so tokens from any version reach the current layout
+    */
+    pub(crate) fn internal_migrate_token(&mut self, token_id: &TokenId, from_version: u16) {
+        /*
+            version 3 stores only the subscription type and the dates of each token, the rest of the metadata comes from the template of the type.
+            the dates are in milliseconds, as the standard says, instead of nanoseconds. The tokens were issued when they started,
+            except the permanent ones that don't have a start. It also indexes the tokens by their expiration, by their subscription type
+            and by when they were minted
+        */
+        if from_version < 3 {
+            let metadata = self.token_metadata_by_id.remove(token_id).unwrap();
+            let mut token_data = token_data_from_metadata(&metadata);
+            token_data.starts_at = token_data.starts_at.map(ns_to_ms);
+            token_data.expires_at = token_data.expires_at.map(ns_to_ms);
+            token_data.issued_at = token_data.issued_at.map(ns_to_ms).or(token_data.starts_at);
+            token_data.updated_at = Some(now_ms());
+            self.token_data_by_id.insert(token_id, &token_data);
+            self.internal_index_expiration(token_id, &token_data);
+            if !token_data.type_suscription.is_empty() {
+                self.internal_add_token_to_type(&token_data.type_suscription, token_id);
+            }
+            let owner_id = self.tokens_by_id.get(token_id).unwrap().owner_id;
+            self.tokens_in_order.insert(&token_sequence(token_id), token_id);
+            self.tokens_per_owner_in_order.insert(&(owner_id, token_sequence(token_id)), token_id);
+        }
+    }
+}
+
+//get the version of the layout the state is stored in
+pub(crate) fn stored_state_version() -> u16 {
+    if let Some(version) = env::storage_read(STATE_VERSION_KEY) {
+        return u16::try_from_slice(&version).unwrap();
+    }
+
+    //the layouts from before the version was stored are told apart by the one that deserializes
+    let state = env::storage_read(b"STATE").expect("The contract is not initialized");
+    if ContractV2::try_from_slice(&state).is_ok() {
+        2
+    } else {
+        1
+    }
+}
+
+//store the current version of the layout next to the state
+pub(crate) fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.try_to_vec().unwrap());
+}
+
+/*
+    layouts of the state of previously deployed versions.
+    when a new layout is deployed, copy the current Contract here as the next version
+    and add the From step that upgrades it
+*/
+
+//layout of version 1: the subscriptions contract before storage management
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV1 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub tokens_to_mint_counter: LookupMap<AccountId, TokensCounter>,
+    pub permanent_cost: U128,
+    pub one_year_cost: U128,
+    pub six_months_cost: U128,
+    pub one_month_cost: U128,
+    pub usdt_contract: String,
+}
+
+//layout of version 2: adds storage management, the storage pool, roles, ownership transfers and staged upgrades
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV2 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub tokens_to_mint_counter: LookupMap<AccountId, TokensCounter>,
+    pub permanent_cost: U128,
+    pub one_year_cost: U128,
+    pub six_months_cost: U128,
+    pub one_month_cost: U128,
+    pub usdt_contract: String,
+    pub storage_balances: LookupMap<AccountId, StorageBalance>,
+    pub storage_pool: Balance,
+    pub sponsored_plans: UnorderedSet<String>,
+    pub sponsored_accounts: LookupSet<AccountId>,
+    pub sponsored_storage_per_account: LookupMap<AccountId, Balance>,
+    pub sponsored_storage_total: Balance,
+    pub sponsored_storage_account_cap: Balance,
+    pub sponsored_storage_global_cap: Balance,
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+    pub pending_owner: Option<PendingOwner>,
+    pub ownership_transfer_delay: u64,
+    pub staged_upgrade: Option<StagedUpgrade>,
+    pub upgrade_timelock: u64,
+}
+
+impl From<ContractV1> for ContractV2 {
+    fn from(old_state: ContractV1) -> Self {
+        let mut role_members = LookupMap::new(StorageKey::RoleMembers.try_to_vec().unwrap());
+        //the owner keeps every privilege it had before the roles existed
+        for role in ROLES {
+            let mut members = UnorderedSet::new(StorageKey::RoleMembersInner { role }.try_to_vec().unwrap());
+            members.insert(&old_state.owner_id);
+            role_members.insert(&role, &members);
+        }
+
         Self {
-            owner_id:old_state.owner_id,
+            owner_id: old_state.owner_id,
             tokens_per_owner: old_state.tokens_per_owner,
-            tokens_by_id:old_state.tokens_by_id,
+            tokens_by_id: old_state.tokens_by_id,
             token_metadata_by_id: old_state.token_metadata_by_id,
-            metadata:old_state.metadata,
-            tokens_to_mint_counter:old_state.tokens_to_mint_counter,
+            metadata: old_state.metadata,
+            tokens_to_mint_counter: old_state.tokens_to_mint_counter,
             permanent_cost: old_state.permanent_cost,
             one_year_cost: old_state.one_year_cost,
             six_months_cost: old_state.six_months_cost,
             one_month_cost: old_state.one_month_cost,
-            usdt_contract: old_state.usdt_contract
+            usdt_contract: old_state.usdt_contract,
+            storage_balances: LookupMap::new(StorageKey::StorageBalances.try_to_vec().unwrap()),
+            storage_pool: 0,
+            sponsored_plans: UnorderedSet::new(StorageKey::SponsoredPlans.try_to_vec().unwrap()),
+            sponsored_accounts: LookupSet::new(StorageKey::SponsoredAccounts.try_to_vec().unwrap()),
+            sponsored_storage_per_account: LookupMap::new(StorageKey::SponsoredStoragePerAccount.try_to_vec().unwrap()),
+            sponsored_storage_total: 0,
+            sponsored_storage_account_cap: DEFAULT_SPONSORED_STORAGE_PER_ACCOUNT,
+            sponsored_storage_global_cap: DEFAULT_SPONSORED_STORAGE_TOTAL,
+            role_members,
+            pending_owner: None,
+            ownership_transfer_delay: 0,
+            staged_upgrade: None,
+            //version 2 kept the upgrade timelock in nanoseconds
+            upgrade_timelock: DEFAULT_UPGRADE_TIMELOCK * NANOSECONDS_PER_MILLISECOND,
         }
     }
+}
 
-
-}
\ No newline at end of file
+/*
+    version 3 (current) adds:
+    - pending_upgrade_timelock: a change of the upgrade timelock waiting for the current one
+    - token_migration: the token migration in progress
+    - pause_status: the scopes that are paused
+    - governance, config_proposals, next_config_proposal_id: the governance of the configuration
+    - audit_log, next_audit_entry_id: the audit log of the privileged actions
+    - next_token_id, plan_prefixed_token_ids: the token ID counter
+    - token_storage_payers: who paid for the storage of each token
+    - sweep_retention_period, sweep_bounty: the sweep of expired tokens
+    - expiry_index, expiration_cursor, expiring_soon_cursor, expiring_soon_notice: the expiry index
+    - tokens_per_type: the index per subscription type
+    - token_types_locked: the type locks
+    - receipts, receipts_per_account, next_receipt_id, unlinked_receipts: the receipts of the payments
+    - revenue_per_plan, revenue_per_token, subscribers, unique_subscribers, payments_count, renewals_count,
+      lifetime_subscriptions, expired_subscriptions, pending_credits: the statistics
+    - plan_end_dates: the end dates of the subscription types
+    - token_data_by_id: the tokens stored as their type and dates in milliseconds
+    - plan_templates: the metadata template per subscription type
+    - tokens_in_order, tokens_per_owner_in_order: the token IDs ordered by when they were minted
+    it also sets the IPFS gateway as base URI and keeps the ownership and upgrade times in milliseconds
+*/
+impl From<ContractV2> for Contract {
+    fn from(old_state: ContractV2) -> Self {
+        //no token was burned before this version, so the IDs go from 0 to the amount of tokens
+        let next_token_id = old_state.token_metadata_by_id.len();
+        //the CIDs of the media are resolved through the IPFS gateway if no other base URI was set
+        let mut metadata = old_state.metadata;
+        if let Some(mut contract_metadata) = metadata.get() {
+            if contract_metadata.base_uri.is_none() {
+                contract_metadata.base_uri = Some(IPFS_GATEWAY.to_string());
+                metadata.set(&contract_metadata);
+            }
+        }
+        Self {
+            owner_id: old_state.owner_id,
+            tokens_per_owner: old_state.tokens_per_owner,
+            tokens_by_id: old_state.tokens_by_id,
+            token_metadata_by_id: old_state.token_metadata_by_id,
+            metadata,
+            tokens_to_mint_counter: old_state.tokens_to_mint_counter,
+            permanent_cost: old_state.permanent_cost,
+            one_year_cost: old_state.one_year_cost,
+            six_months_cost: old_state.six_months_cost,
+            one_month_cost: old_state.one_month_cost,
+            usdt_contract: old_state.usdt_contract,
+            storage_balances: old_state.storage_balances,
+            storage_pool: old_state.storage_pool,
+            sponsored_plans: old_state.sponsored_plans,
+            sponsored_accounts: old_state.sponsored_accounts,
+            sponsored_storage_per_account: old_state.sponsored_storage_per_account,
+            sponsored_storage_total: old_state.sponsored_storage_total,
+            sponsored_storage_account_cap: old_state.sponsored_storage_account_cap,
+            sponsored_storage_global_cap: old_state.sponsored_storage_global_cap,
+            role_members: old_state.role_members,
+            //version 2 kept the ownership and upgrade times in nanoseconds
+            pending_owner: old_state.pending_owner.map(|pending_owner| PendingOwner {
+                accept_after: U64(ns_to_ms(pending_owner.accept_after.0)),
+                ..pending_owner
+            }),
+            ownership_transfer_delay: ns_to_ms(old_state.ownership_transfer_delay),
+            staged_upgrade: old_state.staged_upgrade.map(|staged_upgrade| StagedUpgrade {
+                deploy_after: U64(ns_to_ms(staged_upgrade.deploy_after.0)),
+                ..staged_upgrade
+            }),
+            upgrade_timelock: ns_to_ms(old_state.upgrade_timelock),
+            pending_upgrade_timelock: None,
+            token_migration: None,
+            pause_status: PauseStatus::default(),
+            governance: GovernanceMode::Direct,
+            config_proposals: UnorderedMap::new(StorageKey::ConfigProposals.try_to_vec().unwrap()),
+            next_config_proposal_id: 0,
+            audit_log: LookupMap::new(StorageKey::AuditLog.try_to_vec().unwrap()),
+            next_audit_entry_id: 0,
+            next_token_id,
+            plan_prefixed_token_ids: false,
+            token_storage_payers: LookupMap::new(StorageKey::TokenStoragePayers.try_to_vec().unwrap()),
+            sweep_retention_period: DEFAULT_SWEEP_RETENTION_PERIOD,
+            sweep_bounty: DEFAULT_SWEEP_BOUNTY,
+            expiry_index: TreeMap::new(StorageKey::ExpiryIndex.try_to_vec().unwrap()),
+            expiration_cursor: None,
+            expiring_soon_cursor: None,
+            expiring_soon_notice: 0,
+            tokens_per_type: LookupMap::new(StorageKey::TokensPerType.try_to_vec().unwrap()),
+            token_types_locked: UnorderedMap::new(StorageKey::TokenTypesLocked.try_to_vec().unwrap()),
+            receipts: LookupMap::new(StorageKey::Receipts.try_to_vec().unwrap()),
+            receipts_per_account: LookupMap::new(StorageKey::ReceiptsPerAccount.try_to_vec().unwrap()),
+            next_receipt_id: 0,
+            unlinked_receipts: LookupMap::new(StorageKey::UnlinkedReceipts.try_to_vec().unwrap()),
+            //the payments and credits of version 2 can't be counted, the subscriptions are the tokens minted so far
+            revenue_per_plan: UnorderedMap::new(StorageKey::RevenuePerPlan.try_to_vec().unwrap()),
+            revenue_per_token: UnorderedMap::new(StorageKey::RevenuePerToken.try_to_vec().unwrap()),
+            subscribers: LookupSet::new(StorageKey::Subscribers.try_to_vec().unwrap()),
+            unique_subscribers: 0,
+            payments_count: 0,
+            renewals_count: 0,
+            lifetime_subscriptions: next_token_id,
+            expired_subscriptions: 0,
+            pending_credits: 0,
+            plan_end_dates: UnorderedMap::new(StorageKey::PlanEndDates.try_to_vec().unwrap()),
+            token_data_by_id: UnorderedMap::new(StorageKey::TokenDataById.try_to_vec().unwrap()),
+            plan_templates: default_plan_templates(),
+            tokens_in_order: TreeMap::new(StorageKey::TokensInOrder.try_to_vec().unwrap()),
+            tokens_per_owner_in_order: TreeMap::new(StorageKey::TokensPerOwnerInOrder.try_to_vec().unwrap()),
+        }
+    }
+}
@@ -13,6 +13,88 @@ pub const RARE: &str = "QmWp4Mfvv3czXTgk1qjAWmXLwSk4BrbMA5hksdWHz564GR";
 pub const SUPERRARE: &str = "QmaCGBCABiq3cuBKrUb2DSDMikq3VEWK3KLZTdKZFcv1WL";
 pub const LEGENDARY: &str = "Qmcn6Px9qii11fRoPWxaYsGzRwvBfFqwLGL5fr5BjxjyYt";
 
+// sha256 de cada imagen en base64, el que va dentro de su CID (los CID Qm... son 0x12 0x20 seguido del sha256)
+pub const COMMON_HASH: &str = "4vuAHnmBsy89/+3tAjEl3CFzXt0QqsLyqqOZCvWxugg=";
+pub const RARE_HASH: &str = "feLqcAILQ1zEEjwOs4wMKNb3Hofd2cLq14+zNpoNixI=";
+pub const SUPERRARE_HASH: &str = "sCYeVrAUEt8M11hWi8SFUl5ZlwP1hKP+TKfclLnTDd0=";
+pub const LEGENDARY_HASH: &str = "1ogues47gYSB5/41AGa41hrSmdaosOE6CWaMHk4t2dk=";
+
+// Tipos de suscripción disponibles
+pub const PLANS: [&str; 4] = ["one_month", "six_months", "one_year", "permanent"];
+
+pub(crate) fn is_valid_plan(type_suscription: &str) -> bool {
+    PLANS.contains(&type_suscription)
+}
+
+// Prefijos de los IDs de token legibles, en el mismo orden que PLANS (ej. year-000123)
+pub const TOKEN_ID_PREFIXES: [&str; 4] = ["month", "semester", "year", "permanent"];
+
+// Obtener el número del contador con el que se generó el ID de un token (ej. 123 para year-000123), que es el orden en que se minó
+pub(crate) fn token_sequence(token_id: &TokenId) -> u64 {
+    token_id.rsplit('-').next().unwrap().parse().expect("Invalid token ID")
+}
+
+// Obtener el tipo de suscripción de un token a partir de la imagen de su metadata completa (tokens anteriores a las plantillas)
+pub(crate) fn plan_for_metadata(metadata: &TokenMetadata) -> Option<&'static str> {
+    match metadata.media.as_deref() {
+        Some(COMMON) => Some("one_month"),
+        Some(RARE) => Some("six_months"),
+        Some(SUPERRARE) => Some("one_year"),
+        Some(LEGENDARY) => Some("permanent"),
+        _ => None,
+    }
+}
+
+// Duración de cada tipo de suscripción en meses del calendario, la permanente no caduca
+pub(crate) fn plan_duration_months(type_suscription: &str) -> Option<u32> {
+    match type_suscription {
+        "one_month" => Some(1),
+        "six_months" => Some(6),
+        "one_year" => Some(12),
+        _ => None,
+    }
+}
+
+// Fecha fija de caducidad de un tipo de suscripción, para las suscripciones por generación (ej. válida hasta 2027-06-30)
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlanEndDate {
+    pub type_suscription: String,
+    // Fin del día indicado en UTC, en milisegundos
+    pub expires_at: U64,
+}
+
+// Plantilla inicial de la metadata de cada tipo de suscripción
+pub(crate) fn default_plan_template(type_suscription: &str) -> PlanTemplate {
+    let (title, media, media_hash) = match type_suscription {
+        "one_month" => ("Suscripción de 1 Mes", COMMON, COMMON_HASH),
+        "six_months" => ("Suscripción de 6 Meses", RARE, RARE_HASH),
+        "one_year" => ("Suscripción de 1 Año", SUPERRARE, SUPERRARE_HASH),
+        _ => ("Suscripción Permanente", LEGENDARY, LEGENDARY_HASH),
+    };
+    PlanTemplate {
+        title: Some(title.to_string()),
+        description: Some("Este NFT sirve como pase al contenido de Open Web Academy".to_string()),
+        media: Some(media.to_string()),
+        media_hash: Some(Base64VecU8::from(near_sdk::base64::decode(media_hash).unwrap())),
+        extra: None,
+        reference: None,
+        reference_hash: None,
+        updated_at: None,
+    }
+}
+
+// Datos de un token nuevo, sin fechas
+fn new_token_data(type_suscription: &str) -> TokenData {
+    TokenData {
+        type_suscription: type_suscription.to_string(),
+        issued_at: None,
+        starts_at: None,
+        expires_at: None,
+        updated_at: None,
+    }
+}
+
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ExtraData {
@@ -42,12 +124,9 @@ impl Contract {
         return costs;
     }
 
+    // Cambiar los costos. Si el contrato está gobernado por un consejo, se crea una propuesta y los costos no cambian todavía
     pub fn change_costs(&mut self, one_month_cost: U128 , six_months_cost: U128 , one_year_cost: U128 , permanent_cost: U128) -> TokensCosts {
-        self.assert_owner();
-        self.one_month_cost = one_month_cost;
-        self.six_months_cost = six_months_cost;
-        self.one_year_cost = one_year_cost;
-        self.permanent_cost = permanent_cost;
+        self.internal_govern(ConfigChange::Costs { one_month_cost, six_months_cost, one_year_cost, permanent_cost }, Role::PriceManager);
 
         let costs = TokensCosts {
             one_month_cost: self.one_month_cost,
@@ -60,44 +139,28 @@ impl Contract {
     }
 
     pub fn show_pendant_suscriptions(&self) -> TokensCounter{
-        let signer_id = env::signer_account_id();
-        let user_counter = self.tokens_to_mint_counter.get(&signer_id.clone());
-
-        if !user_counter.is_none() {
-            // Ya existe registro de los contadores
-            let mut user_counter = TokensCounter {
-                canbuy: user_counter.clone().unwrap().canbuy,
-                permanent: user_counter.clone().unwrap().permanent,
-                one_year: user_counter.clone().unwrap().one_year,
-                six_months: user_counter.clone().unwrap().six_months,
-                one_month: user_counter.clone().unwrap().one_month
-            };
-
-            return user_counter;
-
-        } else {
-            // No existe registro de los contadores
-            let mut null_counter = TokensCounter {
-                canbuy: true,
-                permanent: 0,
-                one_year: 0,
-                six_months: 0,
-                one_month: 0
-            };
-
-            return null_counter;
-            
-        }
+        self.internal_pending_subscriptions(&env::signer_account_id())
+    }
 
+    // Obtener las suscripciones pendientes de minar de una cuenta
+    pub(crate) fn internal_pending_subscriptions(&self, account_id: &AccountId) -> TokensCounter {
+        // Si no existe registro de los contadores la cuenta no tiene suscripciones pendientes
+        self.tokens_to_mint_counter.get(account_id).unwrap_or(TokensCounter {
+            canbuy: true,
+            permanent: 0,
+            one_year: 0,
+            six_months: 0,
+            one_month: 0
+        })
     }
 
     pub fn show_usdt_contract(&self) -> String {
         return self.usdt_contract.to_string();
     }
 
+    // Cambiar el contrato de USDT. Si el contrato está gobernado por un consejo, se crea una propuesta y el contrato no cambia todavía
     pub fn change_usdt_contract(&mut self, new_contract: String) -> String {
-        self.assert_owner();
-        self.usdt_contract = new_contract;
+        self.internal_govern(ConfigChange::UsdtContract { new_contract }, Role::Treasurer);
         return self.usdt_contract.to_string();
     }
 
@@ -110,6 +173,11 @@ impl Contract {
             env::panic_str("Only the USDT.e contract can call this method");
         }
 
+        // Rechazar el pago si los pagos están pausados, los USDT.e regresan al usuario
+        if self.is_paused(PauseScope::Payments) {
+            return self.internal_refund_payment(&signer_id, amount, self.plan_for_amount(U128(amount)), "The contract is paused for payments");
+        }
+
         log!("contract_id: {}",contract_id.clone());
         log!("signer_id: {}",signer_id.clone());
         log!("sender_id: {}",sender_id.clone());
@@ -118,17 +186,156 @@ impl Contract {
 
         // Validar si la cantidad transferida corresponde a una suscripción, de lo contrario regresar el dinero
         if near_sdk::json_types::U128(amount.clone()) != self.one_month_cost && near_sdk::json_types::U128(amount.clone()) != self.six_months_cost && near_sdk::json_types::U128(amount.clone()) != self.one_year_cost && near_sdk::json_types::U128(amount.clone()) != self.permanent_cost {
-            env::panic_str("The amount sent does not correspond to any subscription");
+            return self.internal_refund_payment(&signer_id, amount, None, "The amount sent does not correspond to any subscription");
         }
 
+        let type_suscription = self.plan_for_amount(U128(amount)).unwrap();
+        // Rechazar el pago si el tipo de suscripción está bloqueado, no se podría minar el token
+        if self.is_type_locked(type_suscription.to_string()) {
+            let reason = format!("The subscription type {} is locked", type_suscription);
+            return self.internal_refund_payment(&signer_id, amount, Some(type_suscription), &reason);
+        }
+        self.internal_log_event(EventLogVariant::PaymentReceived(vec![PaymentLog {
+            account_id: signer_id.to_string(),
+            amount: U128(amount),
+            type_suscription: type_suscription.to_string(),
+        }]));
+
         // Guardar registro en contador
         self.save_mint_counter(signer_id.clone(), near_sdk::json_types::U128(amount.clone()));
 
+        // Guardar el recibo del pago, su storage lo paga el beneficiario (o el pool si está patrocinado)
+        let initial_storage_usage = env::storage_usage();
+        self.internal_add_receipt(&sender_id, &signer_id, type_suscription, U128(amount));
+        self.internal_pay_storage(&signer_id, type_suscription, env::storage_usage() - initial_storage_usage);
+        self.internal_record_payment(&signer_id, type_suscription, amount);
+
         //return near_sdk::PromiseOrValue::Value(true); // Regresar tokens
         PromiseOrValue::Value(U128(0)) // No Regresar tokens
 
     }
 
+    // Regresar el pago completo al usuario y registrar el reembolso
+    pub(crate) fn internal_refund_payment(&self, account_id: &AccountId, amount: Balance, type_suscription: Option<&str>, reason: &str) -> PromiseOrValue<U128> {
+        log!("{}", reason);
+        self.internal_log_event(EventLogVariant::SubscriptionRefunded(vec![RefundLog {
+            account_id: account_id.to_string(),
+            amount: U128(amount),
+            type_suscription: type_suscription.map(|type_suscription| type_suscription.to_string()),
+            reason: reason.to_string(),
+        }]));
+        PromiseOrValue::Value(U128(amount))
+    }
+
+    // Obtener el tipo de suscripción que corresponde a la cantidad pagada
+    pub(crate) fn plan_for_amount(&self, amount: U128) -> Option<&'static str> {
+        if amount == self.one_month_cost {
+            return Some("one_month");
+        }
+        if amount == self.six_months_cost {
+            return Some("six_months");
+        }
+        if amount == self.one_year_cost {
+            return Some("one_year");
+        }
+        if amount == self.permanent_cost {
+            return Some("permanent");
+        }
+        None
+    }
+
+    // Generar el siguiente ID de token. Los IDs nunca se reutilizan, aunque se quemen tokens
+    pub(crate) fn internal_next_token_id(&mut self, type_suscription: &str) -> TokenId {
+        let id = self.next_token_id;
+        self.next_token_id += 1;
+
+        if self.plan_prefixed_token_ids {
+            let prefix = PLANS.iter().position(|plan| *plan == type_suscription).map(|index| TOKEN_ID_PREFIXES[index]).unwrap();
+            format!("{}-{:06}", prefix, id)
+        } else {
+            id.to_string()
+        }
+    }
+
+    // Fijar la fecha (YYYY-MM-DD, en UTC) hasta la que son válidos los tokens de un tipo de suscripción, o quitarla para volver a su duración
+    pub fn set_plan_end_date(&mut self, type_suscription: String, end_date: Option<String>) {
+        self.assert_role(Role::Admin);
+        require!(plan_duration_months(&type_suscription).is_some(), "The subscription type does not expire");
+
+        let old_value = self.plan_end_dates.get(&type_suscription).map(U64);
+        let new_value = match end_date {
+            Some(end_date) => {
+                let expires_at = end_of_date_ms(&end_date);
+                self.plan_end_dates.insert(&type_suscription, &expires_at);
+                Some(U64(expires_at))
+            }
+            None => {
+                self.plan_end_dates.remove(&type_suscription);
+                None
+            }
+        };
+        self.internal_log_admin_action(
+            "set_plan_end_date",
+            old_value.map(|old_value| json!({ "type_suscription": type_suscription, "expires_at": old_value }).to_string()),
+            new_value.map(|new_value| json!({ "type_suscription": type_suscription, "expires_at": new_value }).to_string()),
+        );
+    }
+
+    pub fn get_plan_end_dates(&self) -> Vec<PlanEndDate> {
+        self.plan_end_dates
+            .iter()
+            .map(|(type_suscription, expires_at)| PlanEndDate { type_suscription, expires_at: U64(expires_at) })
+            .collect()
+    }
+
+    // Activar o desactivar los IDs de token con el prefijo del tipo de suscripción (ej. year-000123)
+    pub fn set_plan_prefixed_token_ids(&mut self, enabled: bool) {
+        self.assert_role(Role::Admin);
+        let old_value = self.plan_prefixed_token_ids;
+        self.plan_prefixed_token_ids = enabled;
+        self.internal_log_admin_action(
+            "set_plan_prefixed_token_ids",
+            Some(json!(old_value).to_string()),
+            Some(json!(enabled).to_string()),
+        );
+    }
+
+    pub fn get_next_token_id(&self) -> U64 {
+        U64(self.next_token_id)
+    }
+
+    // Obtener el tipo de suscripción de un token a partir del prefijo de su ID o, si no tiene, de sus datos
+    pub fn plan_for_token(&self, token_id: TokenId) -> Option<String> {
+        if let Some((prefix, _)) = token_id.split_once('-') {
+            if let Some(index) = TOKEN_ID_PREFIXES.iter().position(|token_prefix| *token_prefix == prefix) {
+                return Some(PLANS[index].to_string());
+            }
+        }
+
+        self.internal_token_data(&token_id)
+            .map(|token_data| token_data.type_suscription)
+            .filter(|type_suscription| !type_suscription.is_empty())
+    }
+
+    // Agregar las fechas del token en milisegundos: emisión y, si la suscripción es diferente de permanente, inicio y caducidad.
+    // La caducidad es la fecha fija del tipo de suscripción si tiene una, de lo contrario se suman los meses del calendario
+    fn internal_set_subscription_dates(&self, metadata: &mut TokenData, type_suscription: &str) {
+        let now = now_ms();
+        metadata.issued_at = Some(now);
+        metadata.updated_at = Some(now);
+        if let Some(months) = plan_duration_months(type_suscription) {
+            let expires_at = match self.plan_end_dates.get(&type_suscription.to_string()) {
+                Some(end_date) => {
+                    require!(now < end_date, format!("The subscription type {} already ended", type_suscription));
+                    end_date
+                }
+                None => add_months_ms(now, months),
+            };
+            metadata.starts_at = Some(now);
+            metadata.expires_at = Some(expires_at);
+        }
+    }
+
     //#[private]
     fn save_mint_counter(&mut self, signer_id: AccountId, amount: U128 ) {
         let user_counter = self.tokens_to_mint_counter.get(&signer_id.clone());
@@ -183,88 +390,57 @@ impl Contract {
                 new_counter.permanent = 1;
             }
 
-            // Guardar nuevo registro de contador
+            // Guardar nuevo registro de contador, cobrando el storage al pool o al balance del usuario
+            let initial_storage_usage = env::storage_usage();
             self.tokens_to_mint_counter.insert(&signer_id.clone(), &new_counter);
+            let type_suscription = self.plan_for_amount(amount).unwrap();
+            self.internal_pay_storage(&signer_id, type_suscription, env::storage_usage() - initial_storage_usage);
         }
 
+        // Registrar la suscripción abonada a la cuenta
+        let counter = self.tokens_to_mint_counter.get(&signer_id).unwrap();
+        let type_suscription = self.plan_for_amount(amount).unwrap();
+        let credits = match type_suscription {
+            "one_month" => counter.one_month,
+            "six_months" => counter.six_months,
+            "one_year" => counter.one_year,
+            _ => counter.permanent,
+        };
+        self.internal_log_event(EventLogVariant::CreditGranted(vec![CreditLog {
+            account_id: signer_id.to_string(),
+            type_suscription: type_suscription.to_string(),
+            credits,
+        }]));
     }
 
     #[payable]
     pub fn mint( &mut self, receiver_id: AccountId, type_suscription: String) -> bool {
         log!("receiver_id: {}",receiver_id.clone());
         log!("type_suscription: {}",type_suscription.clone());
-        let deposit = env::attached_deposit(); // 0.1 Nears
-        log!("deposit: {}",deposit.clone());
+        self.assert_not_paused(PauseScope::Minting);
 
-        // Verificar que se envio 0.01 NEAR para cubrir el storage
-        if deposit < 10000000000000000000000 {
-            log!("Debe enviar 0.01 NEAR");
-            return false;
-        }
+        // Abonar el deposito adjunto al balance de storage de quien llama
+        self.internal_deposit_attached_storage();
 
         // Verificar si existe el tipo de suscripción
-        if type_suscription != "one_month".to_string() && type_suscription != "six_months".to_string() && type_suscription != "one_year".to_string() && type_suscription != "permanent".to_string() {
+        if !is_valid_plan(&type_suscription) {
              return false;
         }
 
-        // Verificar si el DAO es quien intenta minar el token, de lo contrario verificar las suscripciones
-        if self.is_owner(&env::predecessor_account_id()){
-            let mut new_token = TokenMetadata {
-                title:  Some("".to_string()), 
-                description:  Some("Este NFT sirve como pase al contenido de Open Web Academy".to_string()),
-                media:  Some("".to_string()),
-                expires_at: None,
-                starts_at: None,
-                copies: None,
-                extra: None,
-                issued_at: None,
-                media_hash: None,
-                reference: None,
-                reference_hash: None,
-                updated_at: None
-            };
-
-            let mut nft_image = "".to_string();
+        // Verificar que el tipo de suscripción no esté bloqueado
+        self.assert_type_not_locked(&type_suscription);
 
-            if type_suscription == "one_month".to_string() {
-                new_token.title = Some("Suscripción de 1 Mes".to_string());
-                nft_image = COMMON.to_string();
-            }
-            if type_suscription == "six_months".to_string() {
-                new_token.title = Some("Suscripción de 6 Meses".to_string());
-                nft_image = RARE.to_string();
-            }
-            if type_suscription == "one_year".to_string() {
-                new_token.title = Some("Suscripción de 1 Año".to_string());
-                nft_image = SUPERRARE.to_string();
-            }
-            if type_suscription == "permanent".to_string() {
-                new_token.title = Some("Suscripción Permanente".to_string());
-                nft_image = LEGENDARY.to_string();
-            }
+        // Verificar si un Minter es quien intenta minar el token, de lo contrario verificar las suscripciones
+        if self.has_role(Role::Minter, env::predecessor_account_id()){
+            // El título, la descripción y la imagen vienen de la plantilla del tipo de suscripción, el token solo guarda sus fechas
+            let mut new_token = new_token_data(&type_suscription);
     
             let initial_storage_usage = env::storage_usage();
     
-            new_token.media = Some(nft_image);
-            let token_id: TokenId = (self.token_metadata_by_id.len()).to_string();
+            let token_id: TokenId = self.internal_next_token_id(&type_suscription);
     
-            // Agregar fechas de caducidad si la suscripción es diferente de permanente
-            if type_suscription != "permanent".to_string() {
-                let actual_epoch = env::block_timestamp();
-                log!("actual_epoch {}",actual_epoch);
-                // Fecha inicial
-                new_token.starts_at = Some(actual_epoch);
-                // Fecha expiración
-                if type_suscription == "one_month".to_string() {
-                    new_token.expires_at = Some(actual_epoch+2592000000000000);
-                }
-                if type_suscription == "six_months".to_string() {
-                    new_token.expires_at = Some(actual_epoch+15552000000000000);
-                }
-                if type_suscription == "one_year".to_string() {
-                    new_token.expires_at = Some(actual_epoch+31536000000000000);
-                }
-            }
+            // Agregar fechas de emisión y caducidad
+            self.internal_set_subscription_dates(&mut new_token, &type_suscription);
     
             // create a royalty map to store in the token
             let mut royalty = HashMap::new();
@@ -287,8 +463,13 @@ impl Contract {
                 "Token already exists"
             );
     
-            //insert the token ID and metadata
-            self.token_metadata_by_id.insert(&token_id, &new_token);
+            //insert the token ID and its data
+            self.token_data_by_id.insert(&token_id, &new_token);
+            self.internal_index_expiration(&token_id, &new_token);
+            self.internal_add_token_to_type(&type_suscription, &token_id);
+            self.tokens_in_order.insert(&token_sequence(&token_id), &token_id);
+            //keep who pays for the storage of the token, so it gets it back when the token is burned
+            self.token_storage_payers.insert(&token_id, &StoragePayer::Account(env::predecessor_account_id()));
     
             //call the internal method for adding the token to the owner
             self.internal_add_token_to_owner(&token.owner_id, &token_id);
@@ -296,8 +477,18 @@ impl Contract {
             //calculate the required storage which was the used - initial
             let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
     
-            //refund any excess storage if the user attached too much. Panic if they didn't attach enough to cover the required.
-            refund_deposit(required_storage_in_bytes);
+            //pay for the storage out of the storage pool if sponsored, otherwise out of the caller's storage balance. Panic if it doesn't cover the required.
+            let storage_payer = self.internal_pay_storage(&env::predecessor_account_id(), &type_suscription, required_storage_in_bytes);
+            self.token_storage_payers.insert(&token_id, &storage_payer);
+            self.lifetime_subscriptions += 1;
+            self.internal_log_subscription_minted(&token.owner_id, &token_id, &new_token);
+
+            // Registrar el minado sin suscripción en el log de auditoría
+            self.internal_log_admin_action(
+                "minter_mint",
+                None,
+                Some(json!({ "token_id": token_id, "receiver_id": token.owner_id, "type_suscription": type_suscription }).to_string()),
+            );
     
             return true;
         } else {
@@ -318,62 +509,29 @@ impl Contract {
                 one_month: user_counter.clone().unwrap().one_month
             };
     
-            let mut new_token = TokenMetadata {
-                title:  Some("".to_string()), 
-                description:  Some("Este NFT sirve como pase al contenido de Open Web Academy".to_string()),
-                media:  Some("".to_string()),
-                expires_at: None,
-                starts_at: None,
-                copies: None,
-                extra: None,
-                issued_at: None,
-                media_hash: None,
-                reference: None,
-                reference_hash: None,
-                updated_at: None
-            };
-    
-            let mut nft_image = "".to_string();
+            // El título, la descripción y la imagen vienen de la plantilla del tipo de suscripción, el token solo guarda sus fechas
+            let mut new_token = new_token_data(&type_suscription);
 
             // Verificar si tiene suscripción pendiente del tipo que está intentando mintear
             if type_suscription == "one_month".to_string() {
-                if user_counter_data.one_month > 0 { user_counter_data.one_month -= 1; new_token.title = Some("Suscripción de 1 Mes".to_string()); } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
-                nft_image = COMMON.to_string();
+                if user_counter_data.one_month > 0 { user_counter_data.one_month -= 1; } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
             }
             if type_suscription == "six_months".to_string() {
-                if user_counter_data.six_months > 0 { user_counter_data.six_months -= 1; new_token.title = Some("Suscripción de 6 Meses".to_string()); } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
-                nft_image = RARE.to_string();
+                if user_counter_data.six_months > 0 { user_counter_data.six_months -= 1; } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
             }
             if type_suscription == "one_year".to_string() {
-                if user_counter_data.one_year > 0 { user_counter_data.one_year -= 1; new_token.title = Some("Suscripción de 1 Año".to_string()); } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
-                nft_image = SUPERRARE.to_string();
+                if user_counter_data.one_year > 0 { user_counter_data.one_year -= 1; } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
             }
             if type_suscription == "permanent".to_string() {
-                if user_counter_data.permanent > 0 { user_counter_data.permanent -= 1; new_token.title = Some("Suscripción Permanente".to_string()); } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
-                nft_image = LEGENDARY.to_string();
+                if user_counter_data.permanent > 0 { user_counter_data.permanent -= 1; } else { log!("No tiene suscripcion pendiente de: {}", type_suscription.clone()); return false; }
             }
     
             let initial_storage_usage = env::storage_usage();
     
-            new_token.media = Some(nft_image);
-            let token_id: TokenId = (self.token_metadata_by_id.len()).to_string();
+            let token_id: TokenId = self.internal_next_token_id(&type_suscription);
     
-            // Agregar fechas de caducidad si la suscripción es diferente de permanente
-            if type_suscription != "permanent".to_string() {
-                let actual_epoch = env::block_timestamp();
-                // Fecha inicial
-                new_token.starts_at = Some(actual_epoch);
-                // Fecha expiración
-                if type_suscription == "one_month".to_string() {
-                    new_token.expires_at = Some(actual_epoch+2592000000000000);
-                }
-                if type_suscription == "six_months".to_string() {
-                    new_token.expires_at = Some(actual_epoch+15552000000000000);
-                }
-                if type_suscription == "one_year".to_string() {
-                    new_token.expires_at = Some(actual_epoch+31536000000000000);
-                }
-            }
+            // Agregar fechas de emisión y caducidad
+            self.internal_set_subscription_dates(&mut new_token, &type_suscription);
     
             // create a royalty map to store in the token
             let mut royalty = HashMap::new();
@@ -396,35 +554,36 @@ impl Contract {
                 "Token already exists"
             );
     
-            //insert the token ID and metadata
-            self.token_metadata_by_id.insert(&token_id, &new_token);
+            //insert the token ID and its data
+            self.token_data_by_id.insert(&token_id, &new_token);
+            self.internal_index_expiration(&token_id, &new_token);
+            self.internal_add_token_to_type(&type_suscription, &token_id);
+            self.tokens_in_order.insert(&token_sequence(&token_id), &token_id);
+            //keep who pays for the storage of the token, so it gets it back when the token is burned
+            self.token_storage_payers.insert(&token_id, &StoragePayer::Account(env::predecessor_account_id()));
     
             //call the internal method for adding the token to the owner
             self.internal_add_token_to_owner(&token.owner_id, &token_id);
+
+            // Asociar el token al recibo del pago de la suscripción
+            self.internal_link_receipt(&signer_id, &type_suscription, &token_id);
     
             //calculate the required storage which was the used - initial
             let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
     
-            //refund any excess storage if the user attached too much. Panic if they didn't attach enough to cover the required.
-            refund_deposit(required_storage_in_bytes);
+            //pay for the storage out of the storage pool if sponsored, otherwise out of the caller's storage balance. Panic if it doesn't cover the required.
+            let storage_payer = self.internal_pay_storage(&env::predecessor_account_id(), &type_suscription, required_storage_in_bytes);
+            self.token_storage_payers.insert(&token_id, &storage_payer);
     
             // Restar el contador de suscripciones pendientes del usuario
             self.tokens_to_mint_counter.insert(&signer_id.clone(), &user_counter_data);
+            // Los créditos pagados antes de la versión 3 no están contados
+            self.pending_credits = self.pending_credits.saturating_sub(1);
+            self.lifetime_subscriptions += 1;
+            self.internal_log_subscription_minted(&token.owner_id, &token_id, &new_token);
     
             return true;
         }
     }
 
-    fn assert_owner(&self) {
-        require!(self.signer_is_owner(), "Method is private to owner")
-    }
-
-    fn signer_is_owner(&self) -> bool {
-        self.is_owner(&env::predecessor_account_id())
-    }
-
-    fn is_owner(&self, minter: &AccountId) -> bool {
-        minter.as_str() == self.owner_id.as_str()
-    }
-
 }
\ No newline at end of file
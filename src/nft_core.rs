@@ -92,9 +92,9 @@ impl NonFungibleTokenCore for Contract {
             memo,
         );
 
-        //we refund the owner for releasing the storage used up by the approved account IDs
-        refund_approved_account_ids(
-            previous_token.owner_id.clone(),
+        //we give the owner back the storage used up by the approved account IDs
+        self.internal_release_approved_account_ids(
+            &previous_token.owner_id,
             &previous_token.approved_account_ids,
         );
     }
@@ -162,8 +162,8 @@ impl NonFungibleTokenCore for Contract {
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
         //if there is some token ID in the tokens_by_id collection
         if let Some(token) = self.tokens_by_id.get(&token_id) {
-            //we'll get the metadata for that token
-            let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            //we'll put together the metadata for that token from its data and the template of its subscription type
+            let metadata = self.internal_token_metadata(&token_id).unwrap();
             //we return the JsonToken (wrapped by Some since we return an option)
             Some(JsonToken {
                 token_id,
@@ -206,8 +206,8 @@ impl NonFungibleTokenResolver for Contract {
                         since we've already transferred the token and nft_on_transfer returned false, we don't have to 
                         revert the original transfer and thus we can just return true since nothing went wrong.
                     */
-                    //we refund the owner for releasing the storage used up by the approved account IDs
-                    refund_approved_account_ids(owner_id, &approved_account_ids);
+                    //we give the owner back the storage used up by the approved account IDs
+                    self.internal_release_approved_account_ids(&owner_id, &approved_account_ids);
                     return true;
                 }
             }
@@ -216,16 +216,16 @@ impl NonFungibleTokenResolver for Contract {
         //get the token object if there is some token object
         let mut token = if let Some(token) = self.tokens_by_id.get(&token_id) {
             if token.owner_id != receiver_id {
-                //we refund the owner for releasing the storage used up by the approved account IDs
-                refund_approved_account_ids(owner_id, &approved_account_ids);
+                //we give the owner back the storage used up by the approved account IDs
+                self.internal_release_approved_account_ids(&owner_id, &approved_account_ids);
                 // The token is not owner by the receiver anymore. Can't return it.
                 return true;
             }
             token
         //if there isn't a token object, it was burned and so we return true
         } else {
-            //we refund the owner for releasing the storage used up by the approved account IDs
-            refund_approved_account_ids(owner_id, &approved_account_ids);
+            //we give the owner back the storage used up by the approved account IDs
+            self.internal_release_approved_account_ids(&owner_id, &approved_account_ids);
             return true;
         };
 
@@ -237,8 +237,8 @@ impl NonFungibleTokenResolver for Contract {
         //we change the token struct's owner to be the original owner 
         token.owner_id = owner_id.clone();
 
-        //we refund the receiver any approved account IDs that they may have set on the token
-        refund_approved_account_ids(receiver_id.clone(), &token.approved_account_ids);
+        //we give the receiver back the storage of any approved account IDs that they may have set on the token
+        self.internal_release_approved_account_ids(&receiver_id, &token.approved_account_ids);
         //reset the approved account IDs to what they were before the transfer
         token.approved_account_ids = approved_account_ids;
 
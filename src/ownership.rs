@@ -0,0 +1,125 @@
+use crate::*;
+
+//an ownership transfer waiting to be accepted by the new owner
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingOwner {
+    //account that will become the owner once it accepts
+    pub account_id: AccountId,
+    //block timestamp (in milliseconds) from which the transfer can be accepted
+    pub accept_after: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_pending_owner(&self) -> Option<PendingOwner> {
+        self.pending_owner.clone()
+    }
+
+    //start transferring the ownership to a new account. It only takes effect once the new account accepts it
+    pub fn propose_owner(&mut self, new_owner: AccountId) -> PendingOwner {
+        self.assert_owner();
+        require!(new_owner != self.owner_id, "The account is already the owner");
+
+        let pending_owner = PendingOwner {
+            account_id: new_owner,
+            accept_after: U64(now_ms() + self.ownership_transfer_delay),
+        };
+        self.pending_owner = Some(pending_owner.clone());
+        self.internal_audit(
+            "propose_owner",
+            Some(json!(self.owner_id).to_string()),
+            Some(json!(pending_owner).to_string()),
+        );
+
+        self.internal_log_event(EventLogVariant::OwnershipProposed(vec![OwnershipLog {
+            old_owner_id: self.owner_id.to_string(),
+            new_owner_id: pending_owner.account_id.to_string(),
+            accept_after: Some(pending_owner.accept_after),
+        }]));
+
+        pending_owner
+    }
+
+    //called by the proposed account to become the new owner
+    pub fn accept_ownership(&mut self) {
+        let pending_owner = self.pending_owner.clone().expect("There is no ownership transfer in progress");
+        require!(
+            env::predecessor_account_id() == pending_owner.account_id,
+            "Only the proposed owner can accept the ownership"
+        );
+        require!(
+            now_ms() >= pending_owner.accept_after.0,
+            "The ownership can't be accepted yet"
+        );
+
+        //the new owner takes over every role the old owner had
+        let old_owner_id = self.owner_id.clone();
+        for role in ROLES {
+            if self.has_role(role, old_owner_id.clone()) {
+                self.internal_grant_role(role, &pending_owner.account_id);
+                self.internal_revoke_role(role, &old_owner_id);
+            }
+        }
+
+        self.owner_id = pending_owner.account_id;
+        self.pending_owner = None;
+        self.internal_audit(
+            "accept_ownership",
+            Some(json!(old_owner_id).to_string()),
+            Some(json!(self.owner_id).to_string()),
+        );
+
+        self.internal_log_event(EventLogVariant::OwnershipTransferred(vec![OwnershipLog {
+            old_owner_id: old_owner_id.to_string(),
+            new_owner_id: self.owner_id.to_string(),
+            accept_after: None,
+        }]));
+    }
+
+    //cancel the ownership transfer in progress. Can be called by the owner or by the proposed account
+    pub fn cancel_ownership_proposal(&mut self) {
+        let pending_owner = self.pending_owner.clone().expect("There is no ownership transfer in progress");
+        let predecessor_account_id = env::predecessor_account_id();
+        require!(
+            predecessor_account_id == self.owner_id || predecessor_account_id == pending_owner.account_id,
+            "Only the owner or the proposed owner can cancel the ownership transfer"
+        );
+
+        self.pending_owner = None;
+        self.internal_audit("cancel_ownership_proposal", Some(json!(pending_owner).to_string()), None);
+
+        self.internal_log_event(EventLogVariant::OwnershipProposalCancelled(vec![OwnershipLog {
+            old_owner_id: self.owner_id.to_string(),
+            new_owner_id: pending_owner.account_id.to_string(),
+            accept_after: None,
+        }]));
+    }
+
+    //set how long (in milliseconds) the proposed owner has to wait before accepting the ownership
+    pub fn set_ownership_transfer_delay(&mut self, delay: U64) {
+        self.assert_owner();
+        let old_delay = U64(self.ownership_transfer_delay);
+        self.ownership_transfer_delay = delay.0;
+        self.internal_log_admin_action(
+            "set_ownership_transfer_delay",
+            Some(json!(old_delay).to_string()),
+            Some(json!(delay).to_string()),
+        );
+    }
+
+    pub fn get_ownership_transfer_delay(&self) -> U64 {
+        U64(self.ownership_transfer_delay)
+    }
+}
+
+impl Contract {
+    //make sure the predecessor is the owner of the contract
+    pub(crate) fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Method is private to owner")
+    }
+}
@@ -0,0 +1,112 @@
+use crate::*;
+
+//parts of the contract that can be paused independently
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    //ft_on_transfer rejects the payments so the USDT.e goes back to the sender
+    Payments,
+    //mint
+    Minting,
+    //nft_transfer, nft_transfer_call and nft_transfer_payout
+    Transfers,
+    //nft_approve
+    Approvals,
+}
+
+impl PauseScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseScope::Payments => "payments",
+            PauseScope::Minting => "minting",
+            PauseScope::Transfers => "transfers",
+            PauseScope::Approvals => "approvals",
+        }
+    }
+}
+
+//which scopes are currently paused
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseStatus {
+    pub payments: bool,
+    pub minting: bool,
+    pub transfers: bool,
+    pub approvals: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    //pause the passed in scopes
+    pub fn pause(&mut self, scopes: Vec<PauseScope>) -> PauseStatus {
+        self.assert_role(Role::Pauser);
+        let old_status = json!(self.pause_status).to_string();
+        self.internal_set_paused(&scopes, true);
+        self.internal_audit("pause", Some(old_status), Some(json!(self.pause_status).to_string()));
+        self.internal_log_event(EventLogVariant::ContractPaused(vec![PauseLog {
+            account_id: env::predecessor_account_id().to_string(),
+            scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+        }]));
+        //the pause applies to the subscriptions of every type
+        self.internal_log_event(EventLogVariant::SubscriptionPaused(vec![SubscriptionPauseLog {
+            type_suscriptions: PLANS.iter().map(|plan| plan.to_string()).collect(),
+            scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+            until: None,
+        }]));
+        self.pause_status.clone()
+    }
+
+    //unpause the passed in scopes
+    pub fn unpause(&mut self, scopes: Vec<PauseScope>) -> PauseStatus {
+        self.assert_role(Role::Pauser);
+        let old_status = json!(self.pause_status).to_string();
+        self.internal_set_paused(&scopes, false);
+        self.internal_audit("unpause", Some(old_status), Some(json!(self.pause_status).to_string()));
+        self.internal_log_event(EventLogVariant::ContractUnpaused(vec![PauseLog {
+            account_id: env::predecessor_account_id().to_string(),
+            scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+        }]));
+        //the pause applies to the subscriptions of every type
+        self.internal_log_event(EventLogVariant::SubscriptionResumed(vec![SubscriptionPauseLog {
+            type_suscriptions: PLANS.iter().map(|plan| plan.to_string()).collect(),
+            scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+            until: None,
+        }]));
+        self.pause_status.clone()
+    }
+
+    pub fn get_pause_status(&self) -> PauseStatus {
+        self.pause_status.clone()
+    }
+}
+
+impl Contract {
+    pub(crate) fn is_paused(&self, scope: PauseScope) -> bool {
+        match scope {
+            PauseScope::Payments => self.pause_status.payments,
+            PauseScope::Minting => self.pause_status.minting,
+            PauseScope::Transfers => self.pause_status.transfers,
+            PauseScope::Approvals => self.pause_status.approvals,
+        }
+    }
+
+    //make sure the passed in scope is not paused
+    pub(crate) fn assert_not_paused(&self, scope: PauseScope) {
+        require!(
+            !self.is_paused(scope),
+            format!("The contract is paused for {}", scope.as_str())
+        );
+    }
+
+    fn internal_set_paused(&mut self, scopes: &[PauseScope], paused: bool) {
+        for scope in scopes {
+            match scope {
+                PauseScope::Payments => self.pause_status.payments = paused,
+                PauseScope::Minting => self.pause_status.minting = paused,
+                PauseScope::Transfers => self.pause_status.transfers = paused,
+                PauseScope::Approvals => self.pause_status.approvals = paused,
+            }
+        }
+    }
+}
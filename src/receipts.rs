@@ -0,0 +1,156 @@
+use crate::*;
+
+//amount of receipts shown in the overview of an account
+pub const RECENT_RECEIPTS: u64 = 10;
+
+//record of a subscription payment
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Receipt {
+    pub id: u64,
+    //account that sent the tokens
+    pub payer: AccountId,
+    //account credited with the subscription
+    pub beneficiary: AccountId,
+    pub type_suscription: String,
+    //contract of the fungible token used to pay
+    pub token_contract: String,
+    pub amount: U128,
+    //block timestamp in milliseconds
+    pub timestamp: U64,
+    //token minted with the subscription, none until it's minted
+    pub token_id: Option<TokenId>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_receipt(&self, receipt_id: u64) -> Option<Receipt> {
+        self.receipts.get(&receipt_id)
+    }
+
+    //get the receipts of an account, as payer or as beneficiary, oldest first using pagination
+    pub fn get_receipts_for_account(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<Receipt> {
+        let receipt_ids = if let Some(receipt_ids) = self.receipts_per_account.get(&account_id) {
+            receipt_ids
+        } else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0))) as u64;
+        let end = std::cmp::min(receipt_ids.len(), start.saturating_add(limit.unwrap_or(50)));
+
+        (start..end)
+            .map(|index| self.receipts.get(&receipt_ids.get(index).unwrap()).unwrap())
+            .collect()
+    }
+
+    //get the amount of receipts of an account
+    pub fn get_receipts_count_for_account(&self, account_id: AccountId) -> U64 {
+        U64(self.receipts_per_account.get(&account_id).map(|receipt_ids| receipt_ids.len()).unwrap_or(0))
+    }
+}
+
+impl Contract {
+    //append a receipt for a payment to the log of the payer and of the beneficiary and return its ID
+    pub(crate) fn internal_add_receipt(
+        &mut self,
+        payer: &AccountId,
+        beneficiary: &AccountId,
+        type_suscription: &str,
+        amount: U128,
+    ) -> u64 {
+        let receipt = Receipt {
+            id: self.next_receipt_id,
+            payer: payer.clone(),
+            beneficiary: beneficiary.clone(),
+            type_suscription: type_suscription.to_string(),
+            token_contract: self.usdt_contract.clone(),
+            amount,
+            timestamp: U64(now_ms()),
+            token_id: None,
+        };
+        self.receipts.insert(&receipt.id, &receipt);
+        self.next_receipt_id += 1;
+
+        self.internal_add_receipt_to_account(payer, receipt.id);
+        if beneficiary != payer {
+            self.internal_add_receipt_to_account(beneficiary, receipt.id);
+        }
+
+        //keep it with the receipts of the beneficiary for the same subscription type waiting for their token
+        let unlinked_key = (beneficiary.clone(), type_suscription.to_string());
+        let mut unlinked_receipt_ids = self.unlinked_receipts.get(&unlinked_key).unwrap_or_else(|| {
+            Vector::new(
+                StorageKey::UnlinkedReceiptsInner {
+                    account_id_hash: hash_account_id(beneficiary),
+                    token_type_hash: hash_token_type(type_suscription),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        unlinked_receipt_ids.push(&receipt.id);
+        self.unlinked_receipts.insert(&unlinked_key, &unlinked_receipt_ids);
+
+        receipt.id
+    }
+
+    fn internal_add_receipt_to_account(&mut self, account_id: &AccountId, receipt_id: u64) {
+        let mut receipt_ids = self.receipts_per_account.get(account_id).unwrap_or_else(|| {
+            Vector::new(
+                StorageKey::ReceiptsPerAccountInner {
+                    account_id_hash: hash_account_id(account_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        receipt_ids.push(&receipt_id);
+        self.receipts_per_account.insert(account_id, &receipt_ids);
+    }
+
+    //link a minted token to the latest receipt of the beneficiary for the same subscription type that has no token yet
+    pub(crate) fn internal_link_receipt(&mut self, beneficiary: &AccountId, type_suscription: &str, token_id: &TokenId) {
+        let unlinked_key = (beneficiary.clone(), type_suscription.to_string());
+        let mut unlinked_receipt_ids = if let Some(unlinked_receipt_ids) = self.unlinked_receipts.get(&unlinked_key) {
+            unlinked_receipt_ids
+        } else {
+            return;
+        };
+
+        //the oldest payment is linked first, the rest of the ids move one place to the front
+        if let Some(receipt_id) = unlinked_receipt_ids.get(0) {
+            for index in 1..unlinked_receipt_ids.len() {
+                let next_receipt_id = unlinked_receipt_ids.get(index).unwrap();
+                unlinked_receipt_ids.replace(index - 1, &next_receipt_id);
+            }
+            unlinked_receipt_ids.pop();
+
+            let mut receipt = self.receipts.get(&receipt_id).unwrap();
+            receipt.token_id = Some(token_id.clone());
+            self.receipts.insert(&receipt_id, &receipt);
+        }
+
+        if unlinked_receipt_ids.is_empty() {
+            self.unlinked_receipts.remove(&unlinked_key);
+        } else {
+            self.unlinked_receipts.insert(&unlinked_key, &unlinked_receipt_ids);
+        }
+    }
+
+    //get the latest receipts of an account, newest first
+    pub(crate) fn internal_recent_receipts(&self, account_id: &AccountId) -> Vec<Receipt> {
+        self.receipts_per_account
+            .get(account_id)
+            .map(|receipt_ids| {
+                receipt_ids
+                    .iter()
+                    .rev()
+                    .take(RECENT_RECEIPTS as usize)
+                    .map(|receipt_id| self.receipts.get(&receipt_id).unwrap())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
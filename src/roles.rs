@@ -0,0 +1,120 @@
+use crate::*;
+
+//roles that can be granted to accounts to call the privileged methods of the contract
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    //grants and revokes roles
+    Admin,
+    //mints passes without pending subscriptions
+    Minter,
+    //changes the subscription costs
+    PriceManager,
+    //manages the USDT contract and the storage pool
+    Treasurer,
+    //pauses and unpauses the contract
+    Pauser,
+    //upgrades the contract code
+    Upgrader,
+}
+
+pub const ROLES: [Role; 6] = [
+    Role::Admin,
+    Role::Minter,
+    Role::PriceManager,
+    Role::Treasurer,
+    Role::Pauser,
+    Role::Upgrader,
+];
+
+#[near_bindgen]
+impl Contract {
+    //grant a role to an account. Returns false if the account already had the role
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        self.assert_role(Role::Admin);
+        let granted = self.internal_grant_role(role, &account_id);
+        if granted {
+            self.internal_log_admin_action(
+                "grant_role",
+                None,
+                Some(json!({ "role": role, "account_id": account_id }).to_string()),
+            );
+        }
+        granted
+    }
+
+    //revoke a role from an account. Returns false if the account didn't have the role
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        self.assert_role(Role::Admin);
+        let revoked = self.internal_revoke_role(role, &account_id);
+        if revoked {
+            self.internal_log_admin_action(
+                "revoke_role",
+                Some(json!({ "role": role, "account_id": account_id }).to_string()),
+                None,
+            );
+        }
+        revoked
+    }
+
+    //check if an account has been granted a role
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.role_members
+            .get(&role)
+            .map(|members| members.contains(&account_id))
+            .unwrap_or(false)
+    }
+
+    //get the accounts that have been granted a role using pagination
+    pub fn list_role_members(&self, role: Role, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let members = if let Some(members) = self.role_members.get(&role) {
+            members
+        } else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        members.iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    //make sure the predecessor has been granted the passed in role
+    pub(crate) fn assert_role(&self, role: Role) {
+        require!(
+            self.has_role(role, env::predecessor_account_id()),
+            format!("Method requires the {:?} role", role)
+        );
+    }
+
+    pub(crate) fn internal_grant_role(&mut self, role: Role, account_id: &AccountId) -> bool {
+        let mut members = self.role_members.get(&role).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::RoleMembersInner { role }.try_to_vec().unwrap())
+        });
+
+        let granted = members.insert(account_id);
+        self.role_members.insert(&role, &members);
+        granted
+    }
+
+    pub(crate) fn internal_revoke_role(&mut self, role: Role, account_id: &AccountId) -> bool {
+        let mut members = if let Some(members) = self.role_members.get(&role) {
+            members
+        } else {
+            return false;
+        };
+
+        let revoked = members.remove(account_id);
+        //the contract must always keep someone able to manage the roles
+        if role == Role::Admin {
+            require!(!members.is_empty(), "Can't revoke the last Admin");
+        }
+        self.role_members.insert(&role, &members);
+        revoked
+    }
+}
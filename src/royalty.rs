@@ -1,6 +1,6 @@
 use crate::*;
 
-pub trait NonFungibleTokenCore {
+pub trait NonFungibleTokenPayout {
     //calculates the payout for a token given the passed in balance. This is a view method
     fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout;
     
@@ -17,7 +17,7 @@ pub trait NonFungibleTokenCore {
 }
 
 #[near_bindgen]
-impl NonFungibleTokenCore for Contract {
+impl NonFungibleTokenPayout for Contract {
 
     //calculates the payout for a token given the passed in balance. This is a view method
     fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout {
@@ -83,9 +83,9 @@ impl NonFungibleTokenCore for Contract {
             memo,
         );
 
-        //refund the previous token owner for the storage used up by the previous approved account IDs
-        refund_approved_account_ids(
-            previous_token.owner_id.clone(),
+        //give the previous token owner back the storage used up by the previous approved account IDs
+        self.internal_release_approved_account_ids(
+            &previous_token.owner_id,
             &previous_token.approved_account_ids,
         );
 
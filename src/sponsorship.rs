@@ -0,0 +1,180 @@
+use crate::*;
+
+//default amount of storage the pool pays for a single account (0.05 NEAR, roughly ten mints)
+pub const DEFAULT_SPONSORED_STORAGE_PER_ACCOUNT: Balance = 50_000_000_000_000_000_000_000;
+//default amount of storage the pool pays for in total (10 NEAR)
+pub const DEFAULT_SPONSORED_STORAGE_TOTAL: Balance = 10_000_000_000_000_000_000_000_000;
+
+//state of the storage pool used to sponsor the storage of students
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StoragePool {
+    //NEAR left in the pool
+    pub balance: U128,
+    //NEAR the pool has paid for storage so far
+    pub spent: U128,
+    //maximum the pool pays for a single account
+    pub account_cap: U128,
+    //maximum the pool pays in total
+    pub global_cap: U128,
+    //plans whose storage is paid by the pool
+    pub sponsored_plans: Vec<String>,
+}
+
+//who paid for the storage of a token, so it can be given back when the token is burned
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StoragePayer {
+    //the storage balance of the account
+    Account(AccountId),
+    //the storage pool, sponsoring the account
+    Pool(AccountId),
+}
+
+#[near_bindgen]
+impl Contract {
+    //add the attached deposit to the storage pool
+    #[payable]
+    pub fn fund_storage_pool(&mut self) -> U128 {
+        self.assert_role(Role::Treasurer);
+        let old_pool = self.storage_pool;
+        self.storage_pool += env::attached_deposit();
+        self.internal_log_admin_action(
+            "fund_storage_pool",
+            Some(json!(U128(old_pool)).to_string()),
+            Some(json!(U128(self.storage_pool)).to_string()),
+        );
+        U128(self.storage_pool)
+    }
+
+    //take NEAR out of the storage pool and send it to the caller
+    pub fn withdraw_storage_pool(&mut self, amount: U128) -> U128 {
+        self.assert_role(Role::Treasurer);
+        let amount = u128::from(amount);
+        require!(amount <= self.storage_pool, "The amount is greater than the storage pool balance");
+
+        let old_pool = self.storage_pool;
+        self.storage_pool -= amount;
+        Promise::new(env::predecessor_account_id()).transfer(amount);
+        self.internal_log_admin_action(
+            "withdraw_storage_pool",
+            Some(json!(U128(old_pool)).to_string()),
+            Some(json!(U128(self.storage_pool)).to_string()),
+        );
+        U128(self.storage_pool)
+    }
+
+    //set whether the storage of every subscription of a plan is paid by the pool
+    pub fn set_sponsored_plan(&mut self, type_suscription: String, sponsored: bool) {
+        self.assert_role(Role::Treasurer);
+        require!(is_valid_plan(&type_suscription), "The subscription type does not exist");
+        let was_sponsored = if sponsored {
+            !self.sponsored_plans.insert(&type_suscription)
+        } else {
+            self.sponsored_plans.remove(&type_suscription)
+        };
+        self.internal_log_admin_action(
+            "set_sponsored_plan",
+            Some(json!({ "type_suscription": type_suscription, "sponsored": was_sponsored }).to_string()),
+            Some(json!({ "type_suscription": type_suscription, "sponsored": sponsored }).to_string()),
+        );
+    }
+
+    //set whether the storage of an account is paid by the pool, whatever plan it buys
+    pub fn set_sponsored_account(&mut self, account_id: AccountId, sponsored: bool) {
+        self.assert_role(Role::Treasurer);
+        let was_sponsored = if sponsored {
+            !self.sponsored_accounts.insert(&account_id)
+        } else {
+            self.sponsored_accounts.remove(&account_id)
+        };
+        self.internal_log_admin_action(
+            "set_sponsored_account",
+            Some(json!({ "account_id": account_id, "sponsored": was_sponsored }).to_string()),
+            Some(json!({ "account_id": account_id, "sponsored": sponsored }).to_string()),
+        );
+    }
+
+    //set how much the pool can pay for a single account and in total
+    pub fn set_sponsorship_caps(&mut self, account_cap: U128, global_cap: U128) {
+        self.assert_role(Role::Treasurer);
+        let old_caps = json!({
+            "account_cap": U128(self.sponsored_storage_account_cap),
+            "global_cap": U128(self.sponsored_storage_global_cap),
+        });
+        self.sponsored_storage_account_cap = account_cap.into();
+        self.sponsored_storage_global_cap = global_cap.into();
+        self.internal_log_admin_action(
+            "set_sponsorship_caps",
+            Some(old_caps.to_string()),
+            Some(json!({ "account_cap": account_cap, "global_cap": global_cap }).to_string()),
+        );
+    }
+
+    pub fn get_storage_pool(&self) -> StoragePool {
+        StoragePool {
+            balance: U128(self.storage_pool),
+            spent: U128(self.sponsored_storage_total),
+            account_cap: U128(self.sponsored_storage_account_cap),
+            global_cap: U128(self.sponsored_storage_global_cap),
+            sponsored_plans: self.sponsored_plans.to_vec(),
+        }
+    }
+
+    pub fn is_sponsored_account(&self, account_id: AccountId) -> bool {
+        self.sponsored_accounts.contains(&account_id)
+    }
+
+    //how much storage the pool has already paid for the passed in account
+    pub fn sponsored_storage_of(&self, account_id: AccountId) -> U128 {
+        U128(self.sponsored_storage_per_account.get(&account_id).unwrap_or(0))
+    }
+}
+
+impl Contract {
+    /*
+        pay for the storage used by an account for the passed in plan.
+        the pool pays if the plan or the account is sponsored and neither the caps nor the pool balance are exceeded,
+        otherwise the storage is charged to the account's storage balance. Returns who paid
+    */
+    pub(crate) fn internal_pay_storage(&mut self, account_id: &AccountId, type_suscription: &str, storage_used: u64) -> StoragePayer {
+        if storage_used == 0 {
+            return StoragePayer::Account(account_id.clone());
+        }
+
+        let is_sponsored = self.sponsored_plans.contains(&type_suscription.to_string())
+            || self.sponsored_accounts.contains(account_id);
+        if is_sponsored {
+            let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+            let account_spent = self.sponsored_storage_per_account.get(account_id).unwrap_or(0);
+
+            if required_cost <= self.storage_pool
+                && account_spent + required_cost <= self.sponsored_storage_account_cap
+                && self.sponsored_storage_total + required_cost <= self.sponsored_storage_global_cap
+            {
+                self.storage_pool -= required_cost;
+                self.sponsored_storage_total += required_cost;
+                self.sponsored_storage_per_account.insert(account_id, &(account_spent + required_cost));
+                return StoragePayer::Pool(account_id.clone());
+            }
+
+            log!("The storage of {} can't be sponsored, charging the storage balance", account_id);
+        }
+
+        self.internal_charge_storage(account_id, storage_used);
+        StoragePayer::Account(account_id.clone())
+    }
+
+    //give back the passed in amount of storage to whoever paid for it
+    pub(crate) fn internal_refund_storage(&mut self, payer: &StoragePayer, amount: Balance) {
+        match payer {
+            StoragePayer::Account(account_id) => self.internal_release_storage(account_id, amount),
+            StoragePayer::Pool(account_id) => {
+                let account_spent = self.sponsored_storage_per_account.get(account_id).unwrap_or(0);
+                self.storage_pool += amount;
+                self.sponsored_storage_total = self.sponsored_storage_total.saturating_sub(amount);
+                self.sponsored_storage_per_account.insert(account_id, &account_spent.saturating_sub(amount));
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::*;
+
+//business metrics of the contract
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractStats {
+    //amount paid for each subscription type, in the units of the token used to pay
+    pub revenue_per_plan: HashMap<String, U128>,
+    //amount paid with each fungible token contract
+    pub revenue_per_token: HashMap<String, U128>,
+    //tokens that exist and were not marked as expired by process_expirations
+    pub active_subscriptions: U64,
+    //tokens that exist and were marked as expired by process_expirations
+    pub expired_subscriptions: U64,
+    //tokens ever minted, including the burned ones
+    pub lifetime_subscriptions: U64,
+    //accounts that paid at least one subscription
+    pub unique_subscribers: U64,
+    //subscriptions paid and not minted yet
+    pub pending_credits: U64,
+    pub payments: U64,
+    //payments made by accounts that had already paid a subscription
+    pub renewals: U64,
+    //renewals out of the payments, in basis points
+    pub renewal_rate: u16,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        get the metrics of the contract. They are counters kept up to date by the payments, mints, burns and process_expirations,
+        so the expired subscriptions only include the tokens process_expirations already went through
+    */
+    pub fn get_stats(&self) -> ContractStats {
+        ContractStats {
+            revenue_per_plan: self.revenue_per_plan.iter().map(|(plan, amount)| (plan, U128(amount))).collect(),
+            revenue_per_token: self.revenue_per_token.iter().map(|(token, amount)| (token, U128(amount))).collect(),
+            active_subscriptions: U64(self.nft_total_supply().0 as u64 - self.expired_subscriptions),
+            expired_subscriptions: U64(self.expired_subscriptions),
+            lifetime_subscriptions: U64(self.lifetime_subscriptions),
+            unique_subscribers: U64(self.unique_subscribers),
+            pending_credits: U64(self.pending_credits),
+            payments: U64(self.payments_count),
+            renewals: U64(self.renewals_count),
+            renewal_rate: (self.renewals_count * 10_000).checked_div(self.payments_count).unwrap_or(0) as u16,
+        }
+    }
+}
+
+impl Contract {
+    //count a subscription payment of the beneficiary
+    pub(crate) fn internal_record_payment(&mut self, beneficiary: &AccountId, type_suscription: &str, amount: Balance) {
+        let plan_revenue = self.revenue_per_plan.get(&type_suscription.to_string()).unwrap_or(0);
+        self.revenue_per_plan.insert(&type_suscription.to_string(), &(plan_revenue + amount));
+        let token_revenue = self.revenue_per_token.get(&self.usdt_contract).unwrap_or(0);
+        self.revenue_per_token.insert(&self.usdt_contract, &(token_revenue + amount));
+
+        self.payments_count += 1;
+        if self.subscribers.insert(beneficiary) {
+            self.unique_subscribers += 1;
+        } else {
+            self.renewals_count += 1;
+        }
+        self.pending_credits += 1;
+    }
+}
@@ -0,0 +1,253 @@
+use crate::*;
+
+/*
+    bytes taken up by a storage balance record: 40 bytes of trie overhead, the collection prefix,
+    the borsh-encoded account ID (4 bytes for the length plus at most 64 characters) and the two u128 balances
+*/
+pub const STORAGE_REGISTRATION_BYTES: u64 = 40 + 1 + 4 + 64 + 32;
+
+//the storage balance of an account as defined by NEP-145
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    //everything the account has deposited, including what is already locked for storage
+    pub total: U128,
+    //the part of the deposit that can still be used to pay for storage or be withdrawn
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+pub trait StorageManagement {
+    //deposit NEAR to pay for the storage of the passed in account (or the caller if none is passed)
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance;
+
+    //withdraw the passed in amount (or everything available) from the caller's storage balance
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
+    //unregister the caller and refund their storage balance
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+
+    //get the minimum and maximum storage balance an account can have
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+
+    //get the storage balance of the passed in account
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+}
+
+//cost of registering an account for storage
+pub(crate) fn storage_registration_cost() -> Balance {
+    env::storage_byte_cost() * Balance::from(STORAGE_REGISTRATION_BYTES)
+}
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        //get the amount attached and the account that will own the balance
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_cost = storage_registration_cost();
+
+        if registration_only.unwrap_or(false) {
+            if self.storage_balances.get(&account_id).is_some() {
+                //the account is already registered so we refund the whole deposit
+                log!("The account is already registered, refunding the deposit");
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else {
+                //only keep what is needed to register the account and refund the rest
+                assert!(
+                    amount >= registration_cost,
+                    "Must attach at least {} yoctoNEAR to register the account",
+                    registration_cost,
+                );
+                self.internal_storage_deposit(&account_id, registration_cost);
+                let refund = amount - registration_cost;
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+            }
+        } else {
+            self.internal_storage_deposit(&account_id, amount);
+        }
+
+        self.storage_balances.get(&account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        //assert that the user attached exactly 1 yoctoNEAR for security reasons
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut balance = self
+            .storage_balances
+            .get(&account_id)
+            .expect("The account is not registered");
+
+        //withdraw everything available if no amount was passed in
+        let available = u128::from(balance.available);
+        let amount = amount.map(u128::from).unwrap_or(available);
+        assert!(
+            amount <= available,
+            "The amount is greater than the available storage balance"
+        );
+
+        balance.total = U128(u128::from(balance.total) - amount);
+        balance.available = U128(available - amount);
+        self.storage_balances.insert(&account_id, &balance);
+
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        balance
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        //assert that the user attached exactly 1 yoctoNEAR for security reasons
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let balance = if let Some(balance) = self.storage_balances.get(&account_id) {
+            balance
+        } else {
+            log!("The account {} is not registered", account_id);
+            return false;
+        };
+
+        //the part of the balance that is paying for tokens, approvals and history
+        let available = u128::from(balance.available);
+        let in_use = u128::from(balance.total) - available - storage_registration_cost();
+        //if the account still has storage in use, it can only leave by forfeiting it
+        require!(
+            in_use == 0 || force.unwrap_or(false),
+            "Can't unregister the account while its storage balance is in use, use force to forfeit it"
+        );
+
+        //removing the record frees the registration as well so it is refunded together with what is available
+        self.storage_balances.remove(&account_id);
+        Promise::new(account_id).transfer(available + storage_registration_cost());
+
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(storage_registration_cost()),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_balances.get(&account_id)
+    }
+}
+
+impl Contract {
+    //add the passed in amount to the storage balance of an account, registering it if needed
+    pub(crate) fn internal_storage_deposit(&mut self, account_id: &AccountId, amount: Balance) {
+        let balance = if let Some(mut balance) = self.storage_balances.get(account_id) {
+            balance.total = U128(u128::from(balance.total) + amount);
+            balance.available = U128(u128::from(balance.available) + amount);
+            balance
+        } else {
+            //the registration is paid out of the first deposit
+            let registration_cost = storage_registration_cost();
+            assert!(
+                amount >= registration_cost,
+                "Must attach at least {} yoctoNEAR to register the account",
+                registration_cost,
+            );
+            StorageBalance {
+                total: U128(amount),
+                available: U128(amount - registration_cost),
+            }
+        };
+
+        self.storage_balances.insert(account_id, &balance);
+    }
+
+    /*
+        credit the deposit attached to the current call to the caller's storage balance.
+        deposits too small to register a new account are refunded to the caller, except the 1 yoctoNEAR
+        attached for security reasons, which isn't worth a transfer
+    */
+    pub(crate) fn internal_deposit_attached_storage(&mut self) {
+        let amount = env::attached_deposit();
+        let account_id = env::predecessor_account_id();
+        if amount == 0 {
+            return;
+        }
+
+        if self.storage_balances.get(&account_id).is_some() || amount >= storage_registration_cost() {
+            self.internal_storage_deposit(&account_id, amount);
+        } else if amount > 1 {
+            Promise::new(account_id).transfer(amount);
+        }
+    }
+
+    //pay for the passed in amount of bytes out of the available storage balance of an account
+    pub(crate) fn internal_charge_storage(&mut self, account_id: &AccountId, storage_used: u64) {
+        if storage_used == 0 {
+            return;
+        }
+
+        //get how much it would cost to store the information
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        let mut balance = self
+            .storage_balances
+            .get(account_id)
+            .expect("The account is not registered, call storage_deposit first");
+
+        //make sure that the available balance covers the required cost
+        let available = u128::from(balance.available);
+        assert!(
+            required_cost <= available,
+            "Must deposit {} more yoctoNEAR to cover storage",
+            required_cost - available,
+        );
+
+        balance.available = U128(available - required_cost);
+        self.storage_balances.insert(account_id, &balance);
+    }
+
+    /*
+        give back the passed in amount of yoctoNEAR released from storage. It goes back to the available storage balance
+        of the account if it's registered, up to what the balance has in use (ex. storage paid before the account registered
+        was never charged to it), and the rest of the NEAR is sent to the account
+    */
+    pub(crate) fn internal_release_storage(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        let mut refund = amount;
+        if let Some(mut balance) = self.storage_balances.get(account_id) {
+            let available = u128::from(balance.available);
+            let in_use = u128::from(balance.total) - available - storage_registration_cost();
+            let released = std::cmp::min(amount, in_use);
+            balance.available = U128(available + released);
+            self.storage_balances.insert(account_id, &balance);
+            refund -= released;
+        }
+
+        if refund > 0 {
+            Promise::new(account_id.clone()).transfer(refund);
+        }
+    }
+}
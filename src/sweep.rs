@@ -0,0 +1,76 @@
+use crate::*;
+
+//time (in milliseconds) an expired token is kept before it can be swept, 30 days
+pub const DEFAULT_SWEEP_RETENTION_PERIOD: u64 = 30 * MILLISECONDS_PER_DAY;
+//reward for each token swept, taken out of the storage it releases. 0.0005 NEAR
+pub const DEFAULT_SWEEP_BOUNTY: Balance = 500_000_000_000_000_000_000;
+//amount of tokens swept by sweep_expired if no limit is passed
+pub const DEFAULT_SWEEP_LIMIT: u64 = 50;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SweepConfig {
+    pub retention_period: U64,
+    pub bounty: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        burn the tokens whose expiration plus the retention period has passed. Anyone can call it.
+        the tokens are taken from the expiry index, the ones that expired first go first, so up to limit tokens are swept
+        and nothing else is checked. The released storage goes back to whoever paid for it and the caller gets the bounty
+        for each token. Returns the amount of tokens swept
+    */
+    pub fn sweep_expired(&mut self, limit: Option<u64>) -> U64 {
+        //only the tokens that expired before this can be swept
+        let expired_before = now_ms().saturating_sub(self.sweep_retention_period);
+        let mut swept = 0;
+        let mut bounty = 0;
+
+        while swept < limit.unwrap_or(DEFAULT_SWEEP_LIMIT) {
+            let token_id = match self.expiry_index.min() {
+                Some((expires_at, token_id)) if expires_at <= expired_before => token_id,
+                _ => break,
+            };
+
+            //burning the token removes it from the expiry index
+            let (storage_payer, storage_released) = self.internal_burn(&token_id, Some("expired".to_string()));
+            let token_bounty = std::cmp::min(self.sweep_bounty, storage_released);
+            self.internal_refund_storage(&storage_payer, storage_released - token_bounty);
+            bounty += token_bounty;
+            swept += 1;
+        }
+
+        if bounty > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(bounty);
+        }
+
+        U64(swept)
+    }
+
+    //set how long expired tokens are kept before being swept and the bounty paid for each one
+    pub fn set_sweep_config(&mut self, retention_period: U64, bounty: U128) {
+        self.assert_role(Role::Treasurer);
+        let old_config = json!(self.get_sweep_config());
+        self.sweep_retention_period = retention_period.0;
+        self.sweep_bounty = bounty.0;
+        self.internal_log_admin_action(
+            "set_sweep_config",
+            Some(old_config.to_string()),
+            Some(json!(self.get_sweep_config()).to_string()),
+        );
+    }
+
+    pub fn get_sweep_config(&self) -> SweepConfig {
+        SweepConfig {
+            retention_period: U64(self.sweep_retention_period),
+            bounty: U128(self.sweep_bounty),
+        }
+    }
+
+    //who paid for the storage of a token
+    pub fn get_token_storage_payer(&self, token_id: TokenId) -> Option<StoragePayer> {
+        self.token_storage_payers.get(&token_id)
+    }
+}
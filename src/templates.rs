@@ -0,0 +1,162 @@
+use crate::*;
+
+//length in bytes of the sha256 hashes of the media and the reference
+pub const HASH_LENGTH: usize = 32;
+
+//metadata shared by every token of a subscription type. It's stored once and put together with the dates of each token when it's viewed
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlanTemplate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    //when the template was last changed, Unix epoch in milliseconds. Set by the contract
+    pub updated_at: Option<u64>,
+}
+
+#[near_bindgen]
+impl Contract {
+    //replace the template of a subscription type, changing the metadata of all its tokens at once (ex. to update the artwork)
+    pub fn set_plan_template(&mut self, type_suscription: String, template: PlanTemplate) -> PlanTemplate {
+        self.assert_role(Role::Admin);
+        self.internal_set_plan_template("set_plan_template", &type_suscription, template)
+    }
+
+    //set the image of a subscription type with the sha256 hash of its content, so wallets can verify it
+    pub fn set_plan_media(&mut self, type_suscription: String, media: String, media_hash: Base64VecU8) -> PlanTemplate {
+        self.assert_role(Role::Admin);
+        let template = self.get_plan_template(type_suscription.clone()).expect("The subscription type does not exist");
+        self.internal_set_plan_template(
+            "set_plan_media",
+            &type_suscription,
+            PlanTemplate {
+                media: Some(media),
+                media_hash: Some(media_hash),
+                ..template
+            },
+        )
+    }
+
+    //set the off-chain JSON of a subscription type (benefits, course list, terms) with the sha256 hash of its content
+    pub fn set_plan_reference(&mut self, type_suscription: String, reference: String, reference_hash: Base64VecU8) -> PlanTemplate {
+        self.assert_role(Role::Admin);
+        let template = self.get_plan_template(type_suscription.clone()).expect("The subscription type does not exist");
+        self.internal_set_plan_template(
+            "set_plan_reference",
+            &type_suscription,
+            PlanTemplate {
+                reference: Some(reference),
+                reference_hash: Some(reference_hash),
+                ..template
+            },
+        )
+    }
+
+    //set the gateway that resolves the media and the reference of the tokens
+    pub fn set_base_uri(&mut self, base_uri: Option<String>) {
+        self.assert_role(Role::Admin);
+        let mut contract_metadata = self.metadata.get().unwrap();
+        let old_base_uri = std::mem::replace(&mut contract_metadata.base_uri, base_uri);
+        self.metadata.set(&contract_metadata);
+        self.internal_log_admin_action("set_base_uri", old_base_uri, contract_metadata.base_uri);
+    }
+
+    pub fn get_plan_template(&self, type_suscription: String) -> Option<PlanTemplate> {
+        self.plan_templates.get(&type_suscription)
+    }
+
+    pub fn get_plan_templates(&self) -> HashMap<String, PlanTemplate> {
+        self.plan_templates.iter().collect()
+    }
+}
+
+impl Contract {
+    //validate and store the template of a subscription type, logging the change with the passed in action
+    fn internal_set_plan_template(&mut self, action: &str, type_suscription: &str, template: PlanTemplate) -> PlanTemplate {
+        require!(is_valid_plan(type_suscription), "The subscription type does not exist");
+        assert_valid_hash(&template.media_hash, "media");
+        assert_valid_hash(&template.reference_hash, "reference");
+        require!(
+            template.reference.is_none() || template.reference_hash.is_some(),
+            "The reference hash is required if there is a reference"
+        );
+
+        let template = PlanTemplate {
+            updated_at: Some(now_ms()),
+            ..template
+        };
+        let old_template = self.plan_templates.insert(&type_suscription.to_string(), &template);
+        self.internal_log_admin_action(
+            action,
+            old_template.map(|old_template| json!({ "type_suscription": type_suscription, "template": old_template }).to_string()),
+            Some(json!({ "type_suscription": type_suscription, "template": template }).to_string()),
+        );
+        template
+    }
+
+    //get the stored data of a token. The tokens the token migration didn't rewrite yet still have their full metadata
+    pub(crate) fn internal_token_data(&self, token_id: &TokenId) -> Option<TokenData> {
+        self.token_data_by_id
+            .get(token_id)
+            .or_else(|| self.token_metadata_by_id.get(token_id).map(|metadata| token_data_from_metadata(&metadata)))
+    }
+
+    //put together the full metadata of a token from the template of its subscription type and its dates
+    pub(crate) fn internal_token_metadata(&self, token_id: &TokenId) -> Option<TokenMetadata> {
+        let token_data = match self.token_data_by_id.get(token_id) {
+            Some(token_data) => token_data,
+            None => return self.token_metadata_by_id.get(token_id),
+        };
+        let template = self.plan_templates.get(&token_data.type_suscription);
+
+        Some(TokenMetadata {
+            title: template.as_ref().and_then(|template| template.title.clone()),
+            description: template.as_ref().and_then(|template| template.description.clone()),
+            media: template.as_ref().and_then(|template| template.media.clone()),
+            media_hash: template.as_ref().and_then(|template| template.media_hash.clone()),
+            copies: None,
+            issued_at: token_data.issued_at,
+            expires_at: token_data.expires_at,
+            starts_at: token_data.starts_at,
+            //the metadata of the token changes when its dates or its template do
+            updated_at: std::cmp::max(token_data.updated_at, template.as_ref().and_then(|template| template.updated_at)),
+            extra: template.as_ref().and_then(|template| template.extra.clone()),
+            reference: template.as_ref().and_then(|template| template.reference.clone()),
+            reference_hash: template.and_then(|template| template.reference_hash),
+        })
+    }
+}
+
+//make sure a hash, if there is one, is a sha256 hash
+fn assert_valid_hash(hash: &Option<Base64VecU8>, field: &str) {
+    if let Some(hash) = hash {
+        require!(
+            hash.0.len() == HASH_LENGTH,
+            format!("The {} hash must be a sha256 hash of {} bytes", field, HASH_LENGTH)
+        );
+    }
+}
+
+//get the data to store for a token from its full metadata. The subscription type is told by the image
+pub(crate) fn token_data_from_metadata(metadata: &TokenMetadata) -> TokenData {
+    TokenData {
+        type_suscription: plan_for_metadata(metadata).unwrap_or_default().to_string(),
+        issued_at: metadata.issued_at,
+        starts_at: metadata.starts_at,
+        expires_at: metadata.expires_at,
+        updated_at: metadata.updated_at,
+    }
+}
+
+//create the templates of every subscription type with their initial metadata
+pub(crate) fn default_plan_templates() -> UnorderedMap<String, PlanTemplate> {
+    let mut plan_templates = UnorderedMap::new(StorageKey::PlanTemplates.try_to_vec().unwrap());
+    for type_suscription in PLANS {
+        plan_templates.insert(&type_suscription.to_string(), &default_plan_template(type_suscription));
+    }
+    plan_templates
+}
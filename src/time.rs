@@ -0,0 +1,87 @@
+use crate::*;
+
+/*
+    the dates of the token metadata are Unix epoch in milliseconds (NEP-177) while the block timestamp is in nanoseconds.
+    every comparison with the dates of a token goes through now_ms so both sides are in milliseconds
+*/
+pub const NANOSECONDS_PER_MILLISECOND: u64 = 1_000_000;
+pub const MILLISECONDS_PER_DAY: u64 = 86_400_000;
+
+//current block time in milliseconds
+pub(crate) fn now_ms() -> u64 {
+    ns_to_ms(env::block_timestamp())
+}
+
+//convert a timestamp in nanoseconds to milliseconds
+pub(crate) fn ns_to_ms(timestamp: u64) -> u64 {
+    timestamp / NANOSECONDS_PER_MILLISECOND
+}
+
+//check if a year of the Gregorian calendar is a leap year
+pub(crate) fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub(crate) fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/*
+    convert a UTC date to the days since the Unix epoch and back. Both go through the 400-year cycles of the Gregorian calendar,
+    counting the years from March so the leap day is the last day of the year (http://howardhinnant.github.io/date_algorithms.html)
+*/
+pub(crate) fn days_from_date(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub(crate) fn date_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+//add calendar months to a timestamp in milliseconds, keeping the time of the day. The day is moved back to the end of shorter months (ex. January 31 + 1 month = February 28 or 29)
+pub(crate) fn add_months_ms(timestamp: u64, months: u32) -> u64 {
+    let days = (timestamp / MILLISECONDS_PER_DAY) as i64;
+    let time_of_day = timestamp % MILLISECONDS_PER_DAY;
+    let (year, month, day) = date_from_days(days);
+
+    let month_index = i64::from(month) - 1 + i64::from(months);
+    let year = year + month_index / 12;
+    let month = (month_index % 12) as u32 + 1;
+    let day = std::cmp::min(day, days_in_month(year, month));
+    days_from_date(year, month, day) as u64 * MILLISECONDS_PER_DAY + time_of_day
+}
+
+//parse a UTC date in the format YYYY-MM-DD into the timestamp in milliseconds at which that day ends
+pub(crate) fn end_of_date_ms(date: &str) -> u64 {
+    let parts: Vec<&str> = date.split('-').collect();
+    let is_valid_format = parts.len() == 3 && parts[0].len() == 4 && parts[1].len() == 2 && parts[2].len() == 2;
+    let (year, month, day) = match (is_valid_format, parts.as_slice()) {
+        (true, [year, month, day]) => (year.parse::<i64>().unwrap_or(0), month.parse::<u32>().unwrap_or(0), day.parse::<u32>().unwrap_or(0)),
+        _ => (0, 0, 0),
+    };
+    require!(
+        year >= 1970 && (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month),
+        "Invalid date, the format is YYYY-MM-DD"
+    );
+    (days_from_date(year, month, day) + 1) as u64 * MILLISECONDS_PER_DAY
+}